@@ -1,13 +1,18 @@
 const INPUT: &str = include_str!("../input/day02.txt");
 
-#[derive(Clone, Copy)]
+// probabilities indexed by Shape::index (Rock, Paper, Scissors).
+type Distribution = [f64; 3];
+
+const SHAPES: [Shape; 3] = [Shape::Rock, Shape::Paper, Shape::Scissors];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Shape {
     Rock = 1,
     Paper = 2,
     Scissors = 3,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Outcome {
     Win = 6,
     Draw = 3,
@@ -18,6 +23,20 @@ enum Outcome {
 fn main() {
     println!("Part 1 => {}", part_1(INPUT));
     println!("Part 2 => {}", part_2(INPUT));
+
+    let (best_score, worst_score) = score_bounds(iter_opponent_shapes(INPUT));
+    let opponent_distribution = opponent_distribution(INPUT);
+    let guide_strategy = guide_strategy(INPUT);
+    println!("Best possible => {best_score}");
+    println!("Worst possible => {worst_score}");
+    println!(
+        "Expected per round (guide) => {:.3}",
+        expected_score(opponent_distribution, |shape| guide_strategy[shape.index()])
+    );
+    println!(
+        "Expected per round (optimal) => {:.3}",
+        expected_score(opponent_distribution, optimal_strategy)
+    );
 }
 
 fn part_1(input: &str) -> u32 {
@@ -31,8 +50,7 @@ fn part_2(input: &str) -> u32 {
 fn process_line_part_1(input: &str) -> u32 {
     let mut parts = input.split_whitespace().map(get_shape_from_string);
     let (opponent_shape, my_shape) = (parts.next().unwrap(), parts.next().unwrap());
-    let outcome = get_outcome_for_shapes(my_shape, opponent_shape);
-    (my_shape as u32) + (outcome as u32)
+    get_score_for_shapes(my_shape, opponent_shape)
 }
 
 fn process_line_part_2(input: &str) -> u32 {
@@ -45,6 +63,101 @@ fn process_line_part_2(input: &str) -> u32 {
     (my_shape as u32) + (desired_outcome as u32)
 }
 
+// Analysis of a round list or opponent distribution, as opposed to simply scoring the guide.
+fn iter_opponent_shapes(input: &str) -> impl Iterator<Item = Shape> + '_ {
+    input
+        .trim()
+        .lines()
+        .map(|line| get_shape_from_string(line.split_whitespace().next().unwrap()))
+}
+
+fn score_bounds(opponent_shapes: impl Iterator<Item = Shape>) -> (u32, u32) {
+    opponent_shapes.fold((0, 0), |(best, worst), opponent_shape| {
+        let scores = SHAPES.map(|my_shape| get_score_for_shapes(my_shape, opponent_shape));
+        (
+            best + scores.iter().max().unwrap(),
+            worst + scores.iter().min().unwrap(),
+        )
+    })
+}
+
+fn optimal_response(opponent_shape: Shape) -> Shape {
+    SHAPES
+        .into_iter()
+        .max_by_key(|my_shape| get_score_for_shapes(*my_shape, opponent_shape))
+        .unwrap()
+}
+
+fn optimal_strategy(opponent_shape: Shape) -> Distribution {
+    let mut distribution = [0.0; 3];
+    distribution[optimal_response(opponent_shape).index()] = 1.0;
+    distribution
+}
+
+fn opponent_distribution(input: &str) -> Distribution {
+    normalise(
+        iter_opponent_shapes(input).fold([0.0; 3], |mut counts, opponent_shape| {
+            counts[opponent_shape.index()] += 1.0;
+            counts
+        }),
+    )
+}
+
+// the guide's advice (interpreted as in part 1) as a mixed strategy, i.e. for each opponent shape, how
+// often the guide tells us to play each of our shapes.
+fn guide_strategy(input: &str) -> [Distribution; 3] {
+    input
+        .trim()
+        .lines()
+        .fold([[0.0; 3]; 3], |mut counts, line| {
+            let mut parts = line.split_whitespace().map(get_shape_from_string);
+            let (opponent_shape, my_shape) = (parts.next().unwrap(), parts.next().unwrap());
+            counts[opponent_shape.index()][my_shape.index()] += 1.0;
+            counts
+        })
+        .map(normalise)
+}
+
+fn expected_score(
+    opponent_distribution: Distribution,
+    strategy: impl Fn(Shape) -> Distribution,
+) -> f64 {
+    SHAPES
+        .into_iter()
+        .map(|opponent_shape| {
+            let response = strategy(opponent_shape);
+            let expected_for_opponent_shape = SHAPES
+                .into_iter()
+                .map(|my_shape| {
+                    response[my_shape.index()]
+                        * get_score_for_shapes(my_shape, opponent_shape) as f64
+                })
+                .sum::<f64>();
+            opponent_distribution[opponent_shape.index()] * expected_for_opponent_shape
+        })
+        .sum()
+}
+
+fn normalise(counts: Distribution) -> Distribution {
+    let total = counts.iter().sum::<f64>();
+    if total == 0.0 {
+        counts
+    } else {
+        counts.map(|count| count / total)
+    }
+}
+
+impl Shape {
+    fn index(self) -> usize {
+        self as usize - 1
+    }
+}
+
+fn get_score_for_shapes(my_shape: Shape, opponent_shape: Shape) -> u32 {
+    let outcome = get_outcome_for_shapes(my_shape, opponent_shape);
+    (my_shape as u32) + (outcome as u32)
+}
+
 fn get_shape_from_string(input: &str) -> Shape {
     match input {
         "A" | "X" => Shape::Rock,
@@ -148,4 +261,55 @@ mod tests {
         // Assert
         assert_eq!(EXPECTED, output);
     }
+
+    #[test]
+    fn test_score_bounds() {
+        // Arrange
+        const EXPECTED: (u32, u32) = (24, 6);
+
+        // Act
+        let output = score_bounds(iter_opponent_shapes(TEST_INPUT));
+
+        // Assert
+        assert_eq!(EXPECTED, output);
+    }
+
+    #[test]
+    fn test_optimal_response() {
+        // Arrange
+        const EXPECTED: [Shape; 3] = [Shape::Paper, Shape::Scissors, Shape::Rock];
+
+        // Act
+        let output = SHAPES.map(optimal_response);
+
+        // Assert
+        assert_eq!(EXPECTED, output);
+    }
+
+    #[test]
+    fn test_expected_score_guide() {
+        // Arrange
+        const EXPECTED: f64 = 5.0;
+
+        // Act
+        let guide_strategy = guide_strategy(TEST_INPUT);
+        let output = expected_score(opponent_distribution(TEST_INPUT), |shape| {
+            guide_strategy[shape.index()]
+        });
+
+        // Assert
+        assert!((EXPECTED - output).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_expected_score_uniform_against_uniform() {
+        // Arrange
+        const EXPECTED: f64 = 5.0;
+
+        // Act
+        let output = expected_score([1.0 / 3.0; 3], |_| [1.0 / 3.0; 3]);
+
+        // Assert
+        assert!((EXPECTED - output).abs() < 1e-9);
+    }
 }
@@ -1,3 +1,4 @@
+use core::fmt::{self, Display, Formatter};
 use itertools::*;
 
 const INPUT: &str = include_str!("../input/day03.txt");
@@ -6,47 +7,235 @@ const INPUT: &str = include_str!("../input/day03.txt");
 fn main() {
     println!("Part 1 => {}", part_1(INPUT));
     println!("Part 2 => {}", part_2(INPUT));
+    validate(INPUT, 2, 3)
+        .into_iter()
+        .for_each(|anomaly| println!("Anomaly => {anomaly}"));
+}
+
+// problems with the input which the solutions otherwise silently gloss over, line numbers are 1-based.
+#[derive(Debug, PartialEq)]
+enum Anomaly {
+    UnevenLength {
+        line: usize,
+        length: usize,
+    },
+    UnexpectedSharedItemCount {
+        line: usize,
+        items: Vec<char>,
+    },
+    MissingBadge {
+        first_line: usize,
+        last_line: usize,
+    },
+    InvalidCharacter {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+}
+
+impl Anomaly {
+    // the line the anomaly is reported against, the first line for a group.
+    fn line(&self) -> usize {
+        match self {
+            Self::UnevenLength { line, .. }
+            | Self::UnexpectedSharedItemCount { line, .. }
+            | Self::InvalidCharacter { line, .. } => *line,
+            Self::MissingBadge { first_line, .. } => *first_line,
+        }
+    }
 }
 
+impl Display for Anomaly {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnevenLength { line, length } => write!(
+                f,
+                "line {line}: rucksack of length {length} can't be split evenly into compartments"
+            ),
+            Self::UnexpectedSharedItemCount { line, items } => write!(
+                f,
+                "line {line}: expected exactly 1 shared item but found {} {items:?}",
+                items.len()
+            ),
+            Self::MissingBadge {
+                first_line,
+                last_line,
+            } => write!(
+                f,
+                "lines {first_line}-{last_line}: group has no common badge"
+            ),
+            Self::InvalidCharacter {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "line {line}, column {column}: {character:?} is not a valid item"
+            ),
+        }
+    }
+}
+
+// each item is represented by the bit at the index of its priority (1 to 52), so a
+// full set of items fits within a u64 and intersections are simple bitwise ands.
+type ItemMask = u64;
+
 fn part_1(input: &str) -> u32 {
-    input.trim().lines().map(process_line).sum()
+    sum_common_item_priorities_in_rucksacks(input, 2)
 }
 
 fn part_2(input: &str) -> u32 {
+    sum_common_item_priorities_in_groups(input, 3)
+}
+
+fn sum_common_item_priorities_in_rucksacks(input: &str, compartments: usize) -> u32 {
+    input
+        .trim()
+        .lines()
+        .map(|line| common_items_in_rucksack(line, compartments))
+        .flat_map(iter_item_priorities)
+        .sum()
+}
+
+fn sum_common_item_priorities_in_groups(input: &str, group_size: usize) -> u32 {
+    assert!(group_size > 0, "group size must be non-zero");
     input
         .trim()
         .lines()
-        .chunks(3)
+        .chunks(group_size)
         .into_iter()
-        .map(process_chunk)
+        .map(common_items_in_group)
+        .flat_map(iter_item_priorities)
         .sum()
 }
 
-fn process_line(input: &str) -> u32 {
-    let input = input.trim();
-    let mid_point = input.len() / 2;
-    let (first_half, second_half) = input.split_at(mid_point);
-    first_half
-        .chars()
-        .filter(|character| second_half.contains(*character))
-        .map(char_to_priority)
-        .next()
-        .unwrap()
+fn validate(input: &str, compartments: usize, group_size: usize) -> Vec<Anomaly> {
+    assert!(compartments > 0, "compartment count must be non-zero");
+    assert!(group_size > 0, "group size must be non-zero");
+    let lines = input
+        .trim()
+        .lines()
+        .map(|line| line.trim())
+        .collect::<Vec<_>>();
+    let rucksack_anomalies = lines.iter().enumerate().flat_map(|(index, line)| {
+        let line_number = index + 1;
+        let invalid_characters = line
+            .chars()
+            .enumerate()
+            .filter(|(_, character)| !is_item(*character))
+            .map(move |(column, character)| Anomaly::InvalidCharacter {
+                line: line_number,
+                column: column + 1,
+                character,
+            });
+        let length = line.chars().count();
+        let shared_items_anomaly = if length % compartments != 0 {
+            Some(Anomaly::UnevenLength {
+                line: line_number,
+                length,
+            })
+        } else {
+            let items = iter_item_priorities(common_items_in_rucksack(line, compartments))
+                .map(priority_to_char)
+                .collect::<Vec<_>>();
+            if items.len() == 1 {
+                None
+            } else {
+                Some(Anomaly::UnexpectedSharedItemCount {
+                    line: line_number,
+                    items,
+                })
+            }
+        };
+        invalid_characters.chain(shared_items_anomaly)
+    });
+    let group_anomalies = lines
+        .chunks(group_size)
+        .enumerate()
+        .filter_map(|(index, group)| {
+            if common_items_in_group(group.iter().copied()) == 0 {
+                let first_line = index * group_size + 1;
+                Some(Anomaly::MissingBadge {
+                    first_line,
+                    last_line: first_line + group.len() - 1,
+                })
+            } else {
+                None
+            }
+        });
+    let mut anomalies = rucksack_anomalies
+        .chain(group_anomalies)
+        .collect::<Vec<_>>();
+    // the sort is stable, so anomalies on the same line keep the order they were found in.
+    anomalies.sort_by_key(Anomaly::line);
+    anomalies
 }
 
-fn process_chunk<'a>(chunk: impl Iterator<Item = &'a str>) -> u32 {
-    let mut chunk = chunk.map(|line| line.trim());
-    let (elf_1, elf_2, elf_3) = (
-        chunk.next().unwrap(),
-        chunk.next().unwrap(),
-        chunk.next().unwrap(),
-    );
-    elf_1
+fn common_items_in_rucksack(input: &str, compartments: usize) -> ItemMask {
+    intersect_item_masks(iter_compartments(input.trim(), compartments).map(item_mask))
+}
+
+fn common_items_in_group<'a>(group: impl Iterator<Item = &'a str>) -> ItemMask {
+    intersect_item_masks(group.map(|line| item_mask(line.trim())))
+}
+
+fn iter_compartments(input: &str, compartments: usize) -> impl Iterator<Item = &str> {
+    assert!(compartments > 0, "compartment count must be non-zero");
+    // split on character rather than byte boundaries so that invalid non-ASCII items can't cause a panic.
+    let boundaries = input
+        .char_indices()
+        .map(|(index, _)| index)
+        .chain(std::iter::once(input.len()))
+        .collect::<Vec<_>>();
+    // when the length doesn't divide evenly the later compartments take an extra item each, so
+    // that no item is dropped.
+    let length = boundaries.len() - 1;
+    (0..compartments).map(move |index| {
+        &input[boundaries[index * length / compartments]
+            ..boundaries[(index + 1) * length / compartments]]
+    })
+}
+
+fn item_mask(input: &str) -> ItemMask {
+    input
         .chars()
-        .filter(|character| elf_2.contains(*character) && elf_3.contains(*character))
-        .map(char_to_priority)
+        .filter(|character| is_item(*character))
+        .fold(0, |mask, character| {
+            mask | (1 << char_to_priority(character))
+        })
+}
+
+// N-way intersection of item masks, an empty collection of masks has no common items.
+fn intersect_item_masks(mut masks: impl Iterator<Item = ItemMask>) -> ItemMask {
+    masks
         .next()
-        .unwrap()
+        .map(|first| masks.fold(first, |common, mask| common & mask))
+        .unwrap_or(0)
+}
+
+fn iter_item_priorities(mut mask: ItemMask) -> impl Iterator<Item = u32> {
+    std::iter::from_fn(move || {
+        if mask == 0 {
+            None
+        } else {
+            let priority = mask.trailing_zeros();
+            mask &= mask - 1;
+            Some(priority)
+        }
+    })
+}
+
+fn is_item(character: char) -> bool {
+    character.is_ascii_alphabetic()
+}
+
+fn priority_to_char(priority: u32) -> char {
+    if priority <= 26 {
+        char::from(b'a' + priority as u8 - 1)
+    } else {
+        char::from(b'A' + priority as u8 - 27)
+    }
 }
 
 fn char_to_priority(character: char) -> u32 {
@@ -118,4 +307,178 @@ mod tests {
         // Assert
         assert_eq!(output, EXPECTED);
     }
+
+    #[test]
+    fn test_common_items_in_rucksack_reports_every_item() {
+        // Arrange
+        const EXPECTED: [u32; 2] = [1, 28];
+
+        // Act
+        let output =
+            iter_item_priorities(common_items_in_rucksack("aBcdaBef", 2)).collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_common_items_in_rucksack_three_compartments() {
+        // Arrange
+        const EXPECTED: [u32; 1] = [26];
+
+        // Act
+        let output =
+            iter_item_priorities(common_items_in_rucksack("abzcdzzef", 3)).collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_common_items_in_uneven_rucksack() {
+        // Arrange
+        const EXPECTED: [u32; 2] = [1, 2];
+
+        // Act
+        let output = iter_item_priorities(common_items_in_rucksack("abcab", 2)).collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_sum_common_item_priorities_in_groups_of_two() {
+        // Arrange
+        const EXPECTED: u32 = 114 + 136 + 121;
+
+        // Act
+        let output = sum_common_item_priorities_in_groups(INPUT, 2);
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_intersect_item_masks_empty() {
+        // Arrange
+        const EXPECTED: ItemMask = 0;
+
+        // Act
+        let output = intersect_item_masks(std::iter::empty());
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_validate_example_has_no_anomalies() {
+        // Act
+        let output = validate(INPUT, 2, 3);
+
+        // Assert
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_validate_reports_anomalies() {
+        // Arrange
+        const INPUT: &str = "
+        abcab
+        abcdef
+        aBcaBc
+        ab-a
+        xx
+        ";
+        let expected = vec![
+            Anomaly::UnevenLength { line: 1, length: 5 },
+            Anomaly::UnexpectedSharedItemCount {
+                line: 2,
+                items: vec![],
+            },
+            Anomaly::UnexpectedSharedItemCount {
+                line: 3,
+                items: vec!['a', 'c', 'B'],
+            },
+            Anomaly::InvalidCharacter {
+                line: 4,
+                column: 3,
+                character: '-',
+            },
+            Anomaly::MissingBadge {
+                first_line: 4,
+                last_line: 5,
+            },
+        ];
+
+        // Act
+        let output = validate(INPUT, 2, 3);
+
+        // Assert
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_priority_to_char_round_trip() {
+        // Act
+        let output = ('a'..='z')
+            .chain('A'..='Z')
+            .all(|character| priority_to_char(char_to_priority(character)) == character);
+
+        // Assert
+        assert!(output);
+    }
+
+    #[test]
+    #[should_panic(expected = "compartment count must be non-zero")]
+    fn test_zero_compartments_is_rejected() {
+        sum_common_item_priorities_in_rucksacks(INPUT, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "group size must be non-zero")]
+    fn test_zero_group_size_is_rejected() {
+        sum_common_item_priorities_in_groups(INPUT, 0);
+    }
+
+    #[test]
+    fn test_validate_reports_anomalies_in_line_order() {
+        // Arrange
+        const INPUT: &str = "
+        ab
+        cd
+        ef
+        aa
+        abcab
+        xx
+        ";
+        let expected = vec![
+            Anomaly::UnexpectedSharedItemCount {
+                line: 1,
+                items: vec![],
+            },
+            Anomaly::MissingBadge {
+                first_line: 1,
+                last_line: 3,
+            },
+            Anomaly::UnexpectedSharedItemCount {
+                line: 2,
+                items: vec![],
+            },
+            Anomaly::UnexpectedSharedItemCount {
+                line: 3,
+                items: vec![],
+            },
+            Anomaly::MissingBadge {
+                first_line: 4,
+                last_line: 6,
+            },
+            Anomaly::UnevenLength { line: 5, length: 5 },
+        ];
+
+        // Act
+        let output = validate(INPUT, 2, 3);
+
+        // Assert
+        assert_eq!(output, expected);
+    }
 }
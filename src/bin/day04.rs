@@ -1,9 +1,344 @@
+use intervals::*;
+
 const INPUT: &str = include_str!("../input/day04.txt");
 
 #[cfg(not(tarpaulin))]
 fn main() {
     println!("Part 1 => {}", part_1(INPUT));
     println!("Part 2 => {}", part_2(INPUT));
+    println!("Total coverage => {}", total_coverage(INPUT));
+    let covered_more_than_once = sections_covered_by_more_than(INPUT, 1);
+    println!(
+        "Sections covered by more than 1 elf => {} in {:?}",
+        covered_more_than_once.len(),
+        covered_more_than_once.iter().collect::<Vec<_>>()
+    );
+    println!(
+        "Redundant assignments => {}",
+        redundant_assignments(INPUT).len()
+    );
+    println!(
+        "Most overlapped assignment => {}",
+        overlapping_assignments(INPUT)
+            .iter()
+            .map(|ids| ids.len())
+            .max()
+            .unwrap_or(0)
+    );
+    println!(
+        "Assignments contained by another => {}",
+        containing_assignments(INPUT)
+            .iter()
+            .filter(|ids| !ids.is_empty())
+            .count()
+    );
+}
+
+mod intervals {
+    // a set of sections, stored as sorted, disjoint and non-adjacent half-open ranges. Sections
+    // are u32 but stored as u64 so that the exclusive end of a range ending at u32::MAX fits.
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct IntervalSet {
+        ranges: Vec<(u64, u64)>,
+    }
+
+    impl IntervalSet {
+        pub fn from_range(range: (u32, u32)) -> Self {
+            std::iter::once(range).collect()
+        }
+
+        // the set of sections covered by more than k of the given (inclusive) ranges.
+        pub fn covered_more_than(ranges: impl Iterator<Item = (u32, u32)>, k: usize) -> Self {
+            let mut events = ranges
+                .flat_map(|range| {
+                    let (start, end) = to_half_open(range);
+                    [(start, 1), (end, -1)]
+                })
+                .collect::<Vec<_>>();
+            events.sort_unstable();
+            let mut coverage = 0_i64;
+            let mut open_from = None;
+            let mut ranges = Vec::new();
+            events.into_iter().for_each(|(position, delta)| {
+                coverage += delta;
+                let is_covered = coverage > k as i64;
+                match (open_from, is_covered) {
+                    (None, true) => open_from = Some(position),
+                    (Some(start), false) => {
+                        ranges.push((start, position));
+                        open_from = None;
+                    }
+                    _ => {}
+                }
+            });
+            Self::from_half_open(ranges)
+        }
+
+        #[cfg(test)]
+        pub fn insert(&mut self, range: (u32, u32)) {
+            *self = self.union(&Self::from_range(range));
+        }
+
+        #[cfg(test)]
+        pub fn union(&self, other: &Self) -> Self {
+            Self::from_half_open(self.ranges.iter().chain(&other.ranges).copied().collect())
+        }
+
+        #[cfg(test)]
+        pub fn intersection(&self, other: &Self) -> Self {
+            let (mut self_index, mut other_index) = (0, 0);
+            let mut ranges = Vec::new();
+            while let (Some(&(self_start, self_end)), Some(&(other_start, other_end))) =
+                (self.ranges.get(self_index), other.ranges.get(other_index))
+            {
+                let (start, end) = (self_start.max(other_start), self_end.min(other_end));
+                if start < end {
+                    ranges.push((start, end));
+                }
+                if self_end < other_end {
+                    self_index += 1;
+                } else {
+                    other_index += 1;
+                }
+            }
+            Self { ranges }
+        }
+
+        pub fn difference(&self, other: &Self) -> Self {
+            let mut other_ranges = other.ranges.iter().peekable();
+            let mut ranges = Vec::new();
+            self.ranges.iter().for_each(|&(start, end)| {
+                let mut start = start;
+                // skip anything in other which finishes before this range starts.
+                while other_ranges
+                    .next_if(|(_, other_end)| *other_end <= start)
+                    .is_some()
+                {}
+                // cut out any of other's ranges which start inside this one.
+                while let Some(&&(other_start, other_end)) = other_ranges.peek() {
+                    if other_start >= end {
+                        break;
+                    }
+                    if other_start > start {
+                        ranges.push((start, other_start));
+                    }
+                    start = start.max(other_end);
+                    if other_end > end {
+                        break;
+                    }
+                    other_ranges.next();
+                }
+                if start < end {
+                    ranges.push((start, end));
+                }
+            });
+            Self { ranges }
+        }
+
+        pub fn is_superset(&self, other: &Self) -> bool {
+            other.difference(self).is_empty()
+        }
+
+        #[cfg(test)]
+        pub fn contains(&self, section: u32) -> bool {
+            let section = section as u64;
+            let index = self.ranges.partition_point(|(_, end)| *end <= section);
+            matches!(self.ranges.get(index), Some((start, _)) if *start <= section)
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.ranges.is_empty()
+        }
+
+        // number of sections in the set.
+        pub fn len(&self) -> u64 {
+            self.ranges.iter().map(|(start, end)| end - start).sum()
+        }
+
+        // the (inclusive) ranges making up the set, in ascending order.
+        pub fn iter(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+            self.ranges
+                .iter()
+                .map(|(start, end)| (*start as u32, (*end - 1) as u32))
+        }
+
+        fn from_half_open(mut ranges: Vec<(u64, u64)>) -> Self {
+            ranges.sort_unstable();
+            let ranges =
+                ranges
+                    .into_iter()
+                    .fold(Vec::<(u64, u64)>::new(), |mut merged, (start, end)| {
+                        match merged.last_mut() {
+                            Some((_, last_end)) if start <= *last_end => {
+                                *last_end = (*last_end).max(end)
+                            }
+                            _ => merged.push((start, end)),
+                        }
+                        merged
+                    });
+            Self { ranges }
+        }
+    }
+
+    impl FromIterator<(u32, u32)> for IntervalSet {
+        fn from_iter<T>(iter: T) -> Self
+        where
+            T: IntoIterator<Item = (u32, u32)>,
+        {
+            Self::from_half_open(iter.into_iter().map(to_half_open).collect())
+        }
+    }
+
+    fn to_half_open((start, end): (u32, u32)) -> (u64, u64) {
+        (start as u64, end as u64 + 1)
+    }
+
+    // a centered interval tree over a fixed collection of (inclusive) ranges, each identified by
+    // its index in the collection it was built from. Alongside the tree we keep the ranges sorted
+    // by start so that overlap queries are a stab at the query start plus a binary search.
+    #[derive(Debug)]
+    pub struct IntervalTree {
+        nodes: Vec<Node>,
+        root: Option<usize>,
+        by_start: Vec<Entry>,
+    }
+
+    // (start, end, id)
+    type Entry = (u32, u32, usize);
+
+    #[derive(Debug)]
+    struct Node {
+        center: u32,
+        left: Option<usize>,
+        right: Option<usize>,
+        // the ranges containing center, sorted by ascending start and by descending end.
+        by_start: Vec<Entry>,
+        by_end: Vec<Entry>,
+    }
+
+    impl IntervalTree {
+        // ids of the ranges containing the given section.
+        pub fn stabbing(&self, section: u32) -> Vec<usize> {
+            let mut ids = Vec::new();
+            self.for_each_stabbing(section, |(_, _, id)| ids.push(id));
+            ids
+        }
+
+        // ids of the ranges sharing at least one section with the given range.
+        pub fn overlapping(&self, (start, end): (u32, u32)) -> Vec<usize> {
+            let mut ids = self.stabbing(start);
+            let first_after_start = self
+                .by_start
+                .partition_point(|(other_start, _, _)| *other_start <= start);
+            ids.extend(
+                self.by_start[first_after_start..]
+                    .iter()
+                    .take_while(|(other_start, _, _)| *other_start <= end)
+                    .map(|(_, _, id)| *id),
+            );
+            ids
+        }
+
+        // ids of the ranges which fully contain the given range.
+        pub fn containing(&self, (start, end): (u32, u32)) -> Vec<usize> {
+            let mut ids = Vec::new();
+            self.for_each_stabbing(start, |(_, other_end, id)| {
+                if other_end >= end {
+                    ids.push(id);
+                }
+            });
+            ids
+        }
+
+        #[cfg(test)]
+        pub fn len(&self) -> usize {
+            self.by_start.len()
+        }
+
+        fn for_each_stabbing(&self, section: u32, mut func: impl FnMut(Entry)) {
+            let mut current = self.root;
+            while let Some(index) = current {
+                let node = &self.nodes[index];
+                if section < node.center {
+                    node.by_start
+                        .iter()
+                        .take_while(|(start, _, _)| *start <= section)
+                        .for_each(|entry| func(*entry));
+                    current = node.left;
+                } else if section > node.center {
+                    node.by_end
+                        .iter()
+                        .take_while(|(_, end, _)| *end >= section)
+                        .for_each(|entry| func(*entry));
+                    current = node.right;
+                } else {
+                    node.by_start.iter().for_each(|entry| func(*entry));
+                    current = None;
+                }
+            }
+        }
+
+        fn build_node(nodes: &mut Vec<Node>, entries: Vec<Entry>) -> Option<usize> {
+            if entries.is_empty() {
+                return None;
+            }
+
+            // split around the median endpoint so the tree stays balanced.
+            let mut endpoints = entries
+                .iter()
+                .flat_map(|(start, end, _)| [*start, *end])
+                .collect::<Vec<_>>();
+            let median_index = endpoints.len() / 2;
+            let center = *endpoints.select_nth_unstable(median_index).1;
+            let (mut left, mut right, mut here) = (Vec::new(), Vec::new(), Vec::new());
+            entries.into_iter().for_each(|entry| {
+                let (start, end, _) = entry;
+                if end < center {
+                    left.push(entry);
+                } else if start > center {
+                    right.push(entry);
+                } else {
+                    here.push(entry);
+                }
+            });
+            let left = Self::build_node(nodes, left);
+            let right = Self::build_node(nodes, right);
+            let mut by_start = here;
+            let mut by_end = by_start.clone();
+            by_start.sort_unstable_by_key(|(start, _, _)| *start);
+            by_end.sort_unstable_by_key(|(_, end, _)| std::cmp::Reverse(*end));
+            nodes.push(Node {
+                center,
+                left,
+                right,
+                by_start,
+                by_end,
+            });
+            Some(nodes.len() - 1)
+        }
+    }
+
+    impl FromIterator<(u32, u32)> for IntervalTree {
+        fn from_iter<T>(iter: T) -> Self
+        where
+            T: IntoIterator<Item = (u32, u32)>,
+        {
+            let entries = iter
+                .into_iter()
+                .enumerate()
+                .map(|(id, (start, end))| (start, end, id))
+                .collect::<Vec<_>>();
+            let mut by_start = entries.clone();
+            by_start.sort_unstable_by_key(|(start, _, _)| *start);
+            let mut nodes = Vec::new();
+            let root = Self::build_node(&mut nodes, entries);
+            Self {
+                nodes,
+                root,
+                by_start,
+            }
+        }
+    }
 }
 
 fn part_1(input: &str) -> u32 {
@@ -24,6 +359,65 @@ fn part_2(input: &str) -> u32 {
         .count() as u32
 }
 
+// Whole-camp questions, treating every assignment (two per line) individually.
+fn total_coverage(input: &str) -> u64 {
+    iter_assignments(input).collect::<IntervalSet>().len()
+}
+
+fn sections_covered_by_more_than(input: &str, k: usize) -> IntervalSet {
+    IntervalSet::covered_more_than(iter_assignments(input), k)
+}
+
+// indices (into iter_assignments) of the assignments whose sections are all covered by other
+// assignments, i.e. every section of theirs is covered by more than 1 elf.
+fn redundant_assignments(input: &str) -> Vec<usize> {
+    let covered_more_than_once = sections_covered_by_more_than(input, 1);
+    iter_assignments(input)
+        .enumerate()
+        .filter_map(|(index, range)| {
+            if covered_more_than_once.is_superset(&IntervalSet::from_range(range)) {
+                Some(index)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// for each assignment, the indices (into iter_assignments) of every other assignment overlapping it.
+fn overlapping_assignments(input: &str) -> Vec<Vec<usize>> {
+    query_other_assignments(input, IntervalTree::overlapping)
+}
+
+// for each assignment, the indices (into iter_assignments) of every other assignment containing it.
+fn containing_assignments(input: &str) -> Vec<Vec<usize>> {
+    query_other_assignments(input, IntervalTree::containing)
+}
+
+fn query_other_assignments(
+    input: &str,
+    query: impl Fn(&IntervalTree, (u32, u32)) -> Vec<usize>,
+) -> Vec<Vec<usize>> {
+    let tree = iter_assignments(input).collect::<IntervalTree>();
+    iter_assignments(input)
+        .enumerate()
+        .map(|(index, range)| {
+            let mut ids = query(&tree, range);
+            ids.retain(|id| *id != index);
+            ids.sort_unstable();
+            ids
+        })
+        .collect()
+}
+
+fn iter_assignments(input: &str) -> impl Iterator<Item = (u32, u32)> + '_ {
+    input
+        .trim()
+        .lines()
+        .map(line_to_ranges)
+        .flat_map(|(a, b)| [a, b])
+}
+
 fn line_to_ranges(input: &str) -> ((u32, u32), (u32, u32)) {
     let mut iter = input.trim().split(',').map(text_to_range);
     (iter.next().unwrap(), iter.next().unwrap())
@@ -54,9 +448,7 @@ fn does_one_overlap_other(ranges: &((u32, u32), (u32, u32))) -> bool {
 }
 
 fn does_first_overlap_second(first: (u32, u32), second: (u32, u32)) -> bool {
-    let first = first.0..=first.1;
-    let second = second.0..=second.1;
-    first.into_iter().any(|elem| second.contains(&elem))
+    first.0 <= second.1 && second.0 <= first.1
 }
 
 #[cfg(test)]
@@ -95,4 +487,170 @@ mod tests {
         // Assert
         assert_eq!(output, EXPECTED);
     }
+
+    #[test]
+    fn test_interval_set_merges_ranges() {
+        // Arrange
+        const EXPECTED: [(u32, u32); 2] = [(1, 7), (9, 9)];
+
+        // Act
+        let output = [(5, 7), (1, 3), (9, 9), (4, 4), (2, 5)]
+            .into_iter()
+            .collect::<IntervalSet>();
+
+        // Assert
+        assert_eq!(output.iter().collect::<Vec<_>>(), EXPECTED);
+        assert_eq!(output.len(), 8);
+    }
+
+    #[test]
+    fn test_interval_set_operations() {
+        // Arrange
+        let first = [(1, 10), (20, 30)].into_iter().collect::<IntervalSet>();
+        let second = [(5, 25), (28, 28)].into_iter().collect::<IntervalSet>();
+
+        // Act
+        let union = first.union(&second);
+        let intersection = first.intersection(&second);
+        let difference = first.difference(&second);
+
+        // Assert
+        assert_eq!(union.iter().collect::<Vec<_>>(), [(1, 30)]);
+        assert_eq!(
+            intersection.iter().collect::<Vec<_>>(),
+            [(5, 10), (20, 25), (28, 28)]
+        );
+        assert_eq!(
+            difference.iter().collect::<Vec<_>>(),
+            [(1, 4), (26, 27), (29, 30)]
+        );
+        assert!(union.is_superset(&first) && union.is_superset(&second));
+        assert!(!first.is_superset(&second));
+        assert!(first.contains(20) && !first.contains(15));
+    }
+
+    #[test]
+    fn test_interval_set_handles_maximum_section() {
+        // Arrange
+        let mut set = IntervalSet::from_range((u32::MAX - 1, u32::MAX));
+
+        // Act
+        set.insert((0, 0));
+
+        // Assert
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            [(0, 0), (u32::MAX - 1, u32::MAX)]
+        );
+        assert!(set.contains(u32::MAX));
+    }
+
+    #[test]
+    fn test_total_coverage() {
+        // Arrange
+        const EXPECTED: u64 = 8;
+
+        // Act
+        let output = total_coverage(INPUT);
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_sections_covered_by_more_than() {
+        // Arrange
+        const EXPECTED: [(u32, u32); 1] = [(3, 7)];
+
+        // Act
+        let output = sections_covered_by_more_than(INPUT, 4);
+
+        // Assert
+        assert_eq!(output.iter().collect::<Vec<_>>(), EXPECTED);
+    }
+
+    #[test]
+    fn test_redundant_assignments() {
+        // Arrange
+        const INPUT: &str = "
+        1-3,2-5
+        4-6,5-7
+        ";
+        const EXPECTED: [usize; 2] = [1, 2];
+
+        // Act
+        let output = redundant_assignments(INPUT);
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_interval_tree_queries() {
+        // Arrange
+        let tree = [(1, 5), (3, 3), (4, 10), (12, 15), (6, 6)]
+            .into_iter()
+            .collect::<IntervalTree>();
+        let sorted = |mut ids: Vec<usize>| {
+            ids.sort_unstable();
+            ids
+        };
+
+        // Act
+        let stabbing = sorted(tree.stabbing(4));
+        let overlapping = sorted(tree.overlapping((5, 12)));
+        let containing = sorted(tree.containing((4, 5)));
+
+        // Assert
+        assert_eq!(stabbing, [0, 2]);
+        assert_eq!(overlapping, [0, 2, 3, 4]);
+        assert_eq!(containing, [0, 2]);
+        assert!(tree.stabbing(11).is_empty());
+    }
+
+    #[test]
+    fn test_interval_tree_matches_pairwise_checks() {
+        // Arrange
+        let ranges = (0..200_u32)
+            .map(|index| {
+                let start = (index * 37) % 101;
+                (start, start + (index * 13) % 17)
+            })
+            .collect::<Vec<_>>();
+        let tree = ranges.iter().copied().collect::<IntervalTree>();
+
+        // Act / Assert
+        assert_eq!(tree.len(), ranges.len());
+        ranges.iter().for_each(|range| {
+            let mut overlapping = tree.overlapping(*range);
+            let mut containing = tree.containing(*range);
+            overlapping.sort_unstable();
+            containing.sort_unstable();
+            let expected_overlapping = (0..ranges.len())
+                .filter(|id| does_first_overlap_second(ranges[*id], *range))
+                .collect::<Vec<_>>();
+            let expected_containing = (0..ranges.len())
+                .filter(|id| does_first_fully_contain_second(ranges[*id], *range))
+                .collect::<Vec<_>>();
+            assert_eq!(overlapping, expected_overlapping);
+            assert_eq!(containing, expected_containing);
+        });
+    }
+
+    #[test]
+    fn test_overlapping_and_containing_assignments() {
+        // Arrange
+        const INPUT: &str = "
+        2-8,3-7
+        6-6,9-9
+        ";
+
+        // Act
+        let overlapping = overlapping_assignments(INPUT);
+        let containing = containing_assignments(INPUT);
+
+        // Assert
+        assert_eq!(overlapping, [vec![1, 2], vec![0, 2], vec![0, 1], vec![]]);
+        assert_eq!(containing, [vec![], vec![0], vec![0, 1], vec![]]);
+    }
 }
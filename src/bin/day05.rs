@@ -1,67 +1,375 @@
+use crane::*;
 use itertools::*;
 
 const INPUT: &str = include_str!("../input/day05.txt");
 
-type Stack = Vec<char>;
+type Crate = String;
+type Stack = Vec<Crate>;
 type Command = (usize, usize, usize); // (amount, from_index, to_index)
 
 #[cfg(not(tarpaulin))]
 fn main() {
     println!("Part 1 => {}", part_1(INPUT));
     println!("Part 2 => {}", part_2(INPUT));
+    let simulator = simulate(INPUT, CrateMover9001).unwrap_or_else(|error| panic!("{error}"));
+    println!("Final stacks =>\n{}", render_stacks(simulator.stacks()));
+    println!(
+        "Tallest stack while rearranging => {}",
+        simulator
+            .replay()
+            .flat_map(|stacks| stacks.into_iter().map(|stack| stack.len()))
+            .max()
+            .unwrap_or(0)
+    );
+    let (stacks, commands) = extract_stacks_and_commands(INPUT).unwrap();
+    let final_stacks = simulator.stacks().to_vec();
+    let tops = final_stacks.iter().map(|stack| stack.last().cloned());
+    match reverse_from_stacks(final_stacks.clone(), &commands, &CrateMover9001) {
+        Ok(reversed) => println!(
+            "Reversing recovers the starting stacks => {}",
+            reversed == stacks
+        ),
+        Err(error) => println!("Reversing the final stacks failed => {error}"),
+    }
+    match reverse_from_tops(tops, &commands, &CrateMover9001) {
+        Ok(partial) => println!(
+            "Starting crates which can't be identified from the tops alone => {}",
+            partial
+                .iter()
+                .flatten()
+                .filter(|elem| elem.is_none())
+                .count()
+        ),
+        Err(error) => println!("Reversing the tops failed => {error}"),
+    }
 }
 
 fn part_1(input: &str) -> String {
-    let (mut stacks, commands) = extract_stacks_and_commands(input);
-    apply_commands_to_stacks_single(commands.into_iter(), &mut stacks);
-    stacks
-        .into_iter()
-        .map(|mut stack| stack.pop().unwrap())
-        .collect()
+    simulate(input, CrateMover9000)
+        .unwrap_or_else(|error| panic!("{error}"))
+        .top_crates()
 }
 
 fn part_2(input: &str) -> String {
-    let (mut stacks, commands) = extract_stacks_and_commands(input);
-    apply_commands_to_stacks_multi(commands.into_iter(), &mut stacks);
-    stacks
-        .into_iter()
-        .map(|mut stack| stack.pop().unwrap())
-        .collect()
+    simulate(input, CrateMover9001)
+        .unwrap_or_else(|error| panic!("{error}"))
+        .top_crates()
 }
 
-fn apply_commands_to_stacks_single(commands: impl Iterator<Item = Command>, stacks: &mut [Stack]) {
-    commands.for_each(|(amount, from_index, to_index)| {
-        (0..amount).for_each(|_| {
-            let elem = stacks[from_index].pop().unwrap();
-            stacks[to_index].push(elem);
-        });
-    });
+fn simulate<Model: CraneModel>(
+    input: &str,
+    model: Model,
+) -> Result<CraneSimulator<Model>, CraneError> {
+    let (stacks, commands) = extract_stacks_and_commands(input)?;
+    let mut simulator = CraneSimulator::new(stacks, model);
+    simulator.apply_all(commands)?;
+    Ok(simulator)
 }
 
-fn apply_commands_to_stacks_multi(commands: impl Iterator<Item = Command>, stacks: &mut [Stack]) {
-    commands.for_each(|(amount, from_index, to_index)| {
-        // extend the destination with the {amount} elements from the end of the source.
-        let source_stack = &stacks[from_index];
-        let slice_from_index = source_stack.len() - amount;
-        let elements_to_extend_with = source_stack[slice_from_index..].to_vec();
-        stacks[to_index].extend(elements_to_extend_with);
-
-        // shrink source to remove the elements we've moved.
-        let source_stack = &mut stacks[from_index];
-        source_stack.resize(slice_from_index, ' ');
-    });
+mod crane {
+    use super::{Command, Crate, Stack};
+    use core::fmt::{self, Display, Formatter};
+
+    pub trait CraneModel {
+        // moves the crates, the command has already been validated against the stacks. Generic
+        // over the crate type so that stacks of partially known crates can be moved too.
+        fn move_crates<T>(&self, stacks: &mut [Vec<T>], command: Command);
+
+        // reverses a previous move_crates. Both crane models undo a move by simply moving the
+        // crates back again, so that's the default.
+        fn unmove_crates<T>(&self, stacks: &mut [Vec<T>], command: Command) {
+            let (amount, from_index, to_index) = command;
+            self.move_crates(stacks, (amount, to_index, from_index));
+        }
+    }
+
+    // moves crates one at a time, so they end up in reverse order.
+    pub struct CrateMover9000;
+
+    impl CraneModel for CrateMover9000 {
+        fn move_crates<T>(&self, stacks: &mut [Vec<T>], (amount, from_index, to_index): Command) {
+            (0..amount).for_each(|_| {
+                let elem = stacks[from_index].pop().unwrap();
+                stacks[to_index].push(elem);
+            });
+        }
+    }
+
+    // moves all the crates at once, so they keep their order.
+    pub struct CrateMover9001;
+
+    impl CraneModel for CrateMover9001 {
+        fn move_crates<T>(&self, stacks: &mut [Vec<T>], (amount, from_index, to_index): Command) {
+            let source_stack = &mut stacks[from_index];
+            let elements_to_extend_with = source_stack.split_off(source_stack.len() - amount);
+            stacks[to_index].extend(elements_to_extend_with);
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum CraneError {
+        MalformedCommand {
+            index: usize,
+            text: String,
+        },
+        NoSuchStack {
+            index: usize,
+            command: Command,
+            stack: usize,
+        },
+        NotEnoughCrates {
+            index: usize,
+            command: Command,
+            available: usize,
+        },
+        NotEnoughCratesToReverse {
+            index: usize,
+            command: Command,
+            available: usize,
+        },
+    }
+
+    impl Display for CraneError {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match self {
+                Self::MalformedCommand { index, text } => write!(
+                    f,
+                    "command {} ({text:?}): expected \"move <amount> from <stack> to <stack>\" with stacks numbered from 1",
+                    index + 1
+                ),
+                Self::NoSuchStack {
+                    index,
+                    command,
+                    stack,
+                } => write!(
+                    f,
+                    "command {} ({}): there is no stack {}",
+                    index + 1,
+                    display_command(*command),
+                    stack + 1
+                ),
+                Self::NotEnoughCrates {
+                    index,
+                    command,
+                    available,
+                } => write!(
+                    f,
+                    "command {} ({}): stack {} only has {available} crates",
+                    index + 1,
+                    display_command(*command),
+                    command.1 + 1
+                ),
+                Self::NotEnoughCratesToReverse {
+                    index,
+                    command,
+                    available,
+                } => write!(
+                    f,
+                    "command {} ({}): can't be reversed as stack {} only has {available} crates",
+                    index + 1,
+                    display_command(*command),
+                    command.2 + 1
+                ),
+            }
+        }
+    }
+
+    // a stack where some of the crates are unknown, as found when reversing from the tops alone.
+    pub type PartialStack = Vec<Option<Crate>>;
+
+    // runs the commands backwards from the final stacks to find the stacks they started from.
+    pub fn reverse_from_stacks(
+        mut stacks: Vec<Stack>,
+        commands: &[Command],
+        model: &impl CraneModel,
+    ) -> Result<Vec<Stack>, CraneError> {
+        commands
+            .iter()
+            .enumerate()
+            .rev()
+            .try_for_each(|(index, command)| {
+                validate_stack_indices(index, *command, stacks.len())?;
+                let (amount, _, to_index) = *command;
+                let available = stacks[to_index].len();
+                if available < amount {
+                    return Err(CraneError::NotEnoughCratesToReverse {
+                        index,
+                        command: *command,
+                        available,
+                    });
+                }
+                model.unmove_crates(&mut stacks, *command);
+                Ok(())
+            })?;
+        Ok(stacks)
+    }
+
+    // runs the commands backwards knowing only the crate on top of each stack at the end, with
+    // None for a stack which ended up empty. Crates which must have existed but can't be
+    // identified are None, so this gives the smallest possible starting stacks. Any stacks found
+    // by filling in the unknown crates, or by adding more crates underneath, will also produce
+    // the same tops.
+    pub fn reverse_from_tops(
+        tops: impl IntoIterator<Item = Option<Crate>>,
+        commands: &[Command],
+        model: &impl CraneModel,
+    ) -> Result<Vec<PartialStack>, CraneError> {
+        let mut stacks = tops
+            .into_iter()
+            .map(|top| top.into_iter().map(Some).collect::<PartialStack>())
+            .collect::<Vec<_>>();
+        commands
+            .iter()
+            .enumerate()
+            .rev()
+            .try_for_each(|(index, command)| {
+                validate_stack_indices(index, *command, stacks.len())?;
+                let (amount, _, to_index) = *command;
+                let destination = &mut stacks[to_index];
+                let missing = amount.saturating_sub(destination.len());
+                destination.splice(0..0, std::iter::repeat_n(None, missing));
+                model.unmove_crates(&mut stacks, *command);
+                Ok(())
+            })?;
+        Ok(stacks)
+    }
+
+    fn validate_stack_indices(
+        index: usize,
+        command: Command,
+        number_of_stacks: usize,
+    ) -> Result<(), CraneError> {
+        let (_, from_index, to_index) = command;
+        match [from_index, to_index]
+            .into_iter()
+            .find(|stack| *stack >= number_of_stacks)
+        {
+            Some(stack) => Err(CraneError::NoSuchStack {
+                index,
+                command,
+                stack,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn display_command((amount, from_index, to_index): Command) -> String {
+        format!("move {amount} from {} to {}", from_index + 1, to_index + 1)
+    }
+
+    // applies commands to the stacks with a given crane model, keeping the history of
+    // applied commands so they can be undone, redone and replayed from the start.
+    pub struct CraneSimulator<Model> {
+        initial: Vec<Stack>,
+        stacks: Vec<Stack>,
+        model: Model,
+        history: Vec<Command>,
+        applied: usize,
+    }
+
+    impl<Model: CraneModel> CraneSimulator<Model> {
+        pub fn new(stacks: Vec<Stack>, model: Model) -> Self {
+            Self {
+                initial: stacks.clone(),
+                stacks,
+                model,
+                history: Vec::new(),
+                applied: 0,
+            }
+        }
+
+        pub fn stacks(&self) -> &[Stack] {
+            &self.stacks
+        }
+
+        pub fn top_crates(&self) -> String {
+            self.stacks
+                .iter()
+                .filter_map(|stack| stack.last())
+                .map(|elem| elem.as_str())
+                .collect()
+        }
+
+        // applies a single command, discarding anything which could've been redone.
+        pub fn apply(&mut self, command: Command) -> Result<(), CraneError> {
+            self.validate(command)?;
+            self.model.move_crates(&mut self.stacks, command);
+            self.history.truncate(self.applied);
+            self.history.push(command);
+            self.applied += 1;
+            Ok(())
+        }
+
+        // applies the commands in order, stopping at the first invalid one.
+        pub fn apply_all(
+            &mut self,
+            commands: impl IntoIterator<Item = Command>,
+        ) -> Result<(), CraneError> {
+            commands
+                .into_iter()
+                .try_for_each(|command| self.apply(command))
+        }
+
+        #[cfg(test)]
+        pub fn undo(&mut self) -> Option<Command> {
+            if self.applied == 0 {
+                None
+            } else {
+                self.applied -= 1;
+                let command = self.history[self.applied];
+                self.model.unmove_crates(&mut self.stacks, command);
+                Some(command)
+            }
+        }
+
+        #[cfg(test)]
+        pub fn redo(&mut self) -> Option<Command> {
+            let command = *self.history.get(self.applied)?;
+            self.model.move_crates(&mut self.stacks, command);
+            self.applied += 1;
+            Some(command)
+        }
+
+        // every state the stacks have been in, from the initial stacks through to the current ones.
+        pub fn replay(&self) -> impl Iterator<Item = Vec<Stack>> + '_ {
+            let initial = self.initial.clone();
+            std::iter::once(initial.clone()).chain(self.history[..self.applied].iter().scan(
+                initial,
+                |stacks, command| {
+                    self.model.move_crates(stacks, *command);
+                    Some(stacks.clone())
+                },
+            ))
+        }
+
+        fn validate(&self, command: Command) -> Result<(), CraneError> {
+            let (amount, from_index, _) = command;
+            let index = self.applied;
+            validate_stack_indices(index, command, self.stacks.len())?;
+            let available = self.stacks[from_index].len();
+            if available < amount {
+                return Err(CraneError::NotEnoughCrates {
+                    index,
+                    command,
+                    available,
+                });
+            }
+            Ok(())
+        }
+    }
 }
 
-fn extract_stacks_and_commands(input: &str) -> (Vec<Stack>, Vec<Command>) {
+fn extract_stacks_and_commands(input: &str) -> Result<(Vec<Stack>, Vec<Command>), CraneError> {
     let groups = input.lines().group_by(|line| line.trim().is_empty());
     let mut sections = groups
         .into_iter()
         .skip_while(|(key, _)| *key)
         .filter_map(|(key, group)| if key { None } else { Some(group) });
-    (
-        extract_stacks(sections.next().unwrap()),
-        extract_commands(sections.next().unwrap()),
-    )
+    let stacks = extract_stacks(sections.next().unwrap());
+    let commands = sections
+        .next()
+        .map_or_else(|| Ok(Vec::new()), extract_commands)?;
+    Ok((stacks, commands))
 }
 
 fn extract_stacks<'a>(input: impl Iterator<Item = &'a str>) -> Vec<Stack> {
@@ -76,28 +384,71 @@ fn extract_stacks<'a>(input: impl Iterator<Item = &'a str>) -> Vec<Stack> {
     // create empty stacks.
     let mut stacks = allocate_stacks(number_of_stacks);
 
-    // for the stack elements themselves, we're essentially taking
-    // groups of 4 characters to process. We can do this with the chunks
-    // method on slices.
-    input.for_each(|line| {
-        line.chars()
-            .chunks(4)
-            .into_iter()
-            .map(extract_stack_element)
-            .enumerate()
-            .for_each(|(index, elem)| {
-                if elem != ' ' {
-                    stacks[index].push(elem);
-                }
-            });
+    // find the bracketed crates on each line along with the column they start in, then the
+    // widest crate tells us the width of each stack's column (the crate plus a separating space).
+    let rows = input.map(extract_crate_row).collect::<Vec<_>>();
+    let column_width = rows
+        .iter()
+        .flatten()
+        .map(|(_, label)| label.chars().count())
+        .max()
+        .unwrap_or(1)
+        + 3;
+    rows.into_iter().for_each(|row| {
+        row.into_iter().for_each(|(column, label)| {
+            stacks[column / column_width].push(label);
+        });
     });
 
     // return initialised stacks.
     stacks
 }
 
-fn extract_stack_element(mut chunk: impl Iterator<Item = char>) -> char {
-    chunk.nth(1).unwrap()
+fn extract_crate_row(input: &str) -> Vec<(usize, Crate)> {
+    let mut row = Vec::new();
+    let mut chars = input.chars().enumerate();
+    while let Some((column, _)) = chars.find(|(_, character)| *character == '[') {
+        let label = chars
+            .by_ref()
+            .map(|(_, character)| character)
+            .take_while(|character| *character != ']')
+            .collect();
+        row.push((column, label));
+    }
+    row
+}
+
+// inverse of extract_stacks, every crate is padded to the width of the widest and stack
+// numbers are centred underneath.
+fn render_stacks(stacks: &[Stack]) -> String {
+    let label_width = stacks
+        .iter()
+        .flatten()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or(1);
+    let box_width = label_width + 2;
+    let tallest = stacks.iter().map(|stack| stack.len()).max().unwrap_or(0);
+    let crate_rows = (0..tallest).rev().map(|height| {
+        stacks
+            .iter()
+            .map(|stack| match stack.get(height) {
+                Some(label) => format!("{:<box_width$}", format!("[{label}]")),
+                None => " ".repeat(box_width),
+            })
+            .join(" ")
+    });
+    let number_row = (1..=stacks.len())
+        .map(|number| {
+            let number = number.to_string();
+            let left_padding = box_width.saturating_sub(number.len()) / 2;
+            format!(
+                "{:<box_width$}",
+                format!("{}{number}", " ".repeat(left_padding))
+            )
+        })
+        .join(" ");
+    crate_rows.chain(std::iter::once(number_row)).join("\n")
 }
 
 fn allocate_stacks(number_of_stacks: usize) -> Vec<Stack> {
@@ -112,26 +463,33 @@ fn extract_number_of_stacks(input: &str) -> usize {
     input
         .split_whitespace()
         .map(|num| num.parse::<usize>().unwrap())
-        .rev()
-        .next()
+        .next_back()
         .unwrap()
 }
 
-fn extract_commands<'a>(input: impl Iterator<Item = &'a str>) -> Vec<Command> {
-    input.map(extract_command).collect()
+fn extract_commands<'a>(input: impl Iterator<Item = &'a str>) -> Result<Vec<Command>, CraneError> {
+    input
+        .enumerate()
+        .map(|(index, line)| extract_command(index, line))
+        .collect()
 }
 
-fn extract_command(input: &str) -> Command {
+fn extract_command(index: usize, input: &str) -> Result<Command, CraneError> {
+    let malformed = || CraneError::MalformedCommand {
+        index,
+        text: input.trim().to_string(),
+    };
     let mut values = input
         .split_whitespace()
         .skip(1)
         .step_by(2)
-        .map(|elem| elem.parse::<usize>().unwrap());
-    (
-        values.next().unwrap(),
-        values.next().unwrap() - 1,
-        values.next().unwrap() - 1,
-    )
+        .map(|elem| elem.parse::<usize>().ok());
+    let mut next_value = || values.next().flatten().ok_or_else(malformed);
+    let amount = next_value()?;
+    // stacks are numbered from 1 in the input, so stack 0 is as malformed as a missing number.
+    let from_index = next_value()?.checked_sub(1).ok_or_else(malformed)?;
+    let to_index = next_value()?.checked_sub(1).ok_or_else(malformed)?;
+    Ok((amount, from_index, to_index))
 }
 
 #[cfg(test)]
@@ -173,4 +531,242 @@ move 1 from 1 to 2
         // Assert
         assert_eq!(&output, EXPECTED);
     }
+
+    #[test]
+    fn test_simulator_reports_invalid_commands() {
+        // Arrange
+        let (stacks, mut commands) = extract_stacks_and_commands(INPUT).unwrap();
+        commands.insert(2, (4, 1, 0));
+        commands.push((1, 0, 3));
+        let mut simulator = CraneSimulator::new(stacks.clone(), CrateMover9000);
+        let mut out_of_range_simulator = CraneSimulator::new(stacks, CrateMover9001);
+
+        // Act
+        let output = simulator.apply_all(commands.clone());
+        let out_of_range_output = out_of_range_simulator.apply_all(
+            commands
+                .into_iter()
+                .enumerate()
+                .filter_map(|(index, command)| if index == 2 { None } else { Some(command) }),
+        );
+
+        // Assert
+        assert_eq!(
+            output,
+            Err(CraneError::NotEnoughCrates {
+                index: 2,
+                command: (4, 1, 0),
+                available: 2,
+            })
+        );
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "command 3 (move 4 from 2 to 1): stack 2 only has 2 crates"
+        );
+        assert_eq!(
+            out_of_range_output,
+            Err(CraneError::NoSuchStack {
+                index: 4,
+                command: (1, 0, 3),
+                stack: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_simulator_undo_redo() {
+        // Arrange
+        let (stacks, commands) = extract_stacks_and_commands(INPUT).unwrap();
+        let mut simulator = CraneSimulator::new(stacks.clone(), CrateMover9001);
+        simulator.apply_all(commands.clone()).unwrap();
+
+        // Act / Assert
+        assert_eq!(simulator.top_crates(), "MCD");
+        while simulator.undo().is_some() {}
+        assert_eq!(simulator.stacks(), stacks);
+        assert_eq!(simulator.redo(), Some(commands[0]));
+        assert_eq!(simulator.redo(), Some(commands[1]));
+        simulator.apply((1, 2, 0)).unwrap();
+        assert_eq!(simulator.redo(), None);
+        assert_eq!(simulator.top_crates(), "DCN");
+    }
+
+    #[test]
+    fn test_simulator_replay() {
+        // Arrange
+        let (stacks, commands) = extract_stacks_and_commands(INPUT).unwrap();
+        let mut simulator = CraneSimulator::new(stacks, CrateMover9000);
+        simulator.apply_all(commands).unwrap();
+
+        // Act
+        let output = simulator.replay().collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(output.len(), 5);
+        assert_eq!(output[1], [vec!["Z", "N", "D"], vec!["M", "C"], vec!["P"]]);
+        assert_eq!(output[4], simulator.stacks());
+    }
+
+    #[test]
+    fn test_render_stacks_round_trip() {
+        // Arrange
+        let expected = INPUT.lines().skip(1).take(4).join("\n");
+
+        // Act
+        let (stacks, _) = extract_stacks_and_commands(INPUT).unwrap();
+        let output = render_stacks(&stacks);
+
+        // Assert
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_render_stacks_round_trip_real_input() {
+        // Arrange
+        let expected = super::INPUT.lines().take(9).join("\n");
+
+        // Act
+        let output = render_stacks(&extract_stacks(expected.lines()));
+
+        // Assert
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_multi_character_labels_and_many_stacks() {
+        // Arrange
+        let stacks = (1..=12)
+            .map(|number| {
+                (0..number % 3)
+                    .map(|height| format!("C{}", number * 10 + height))
+                    .collect::<Stack>()
+            })
+            .collect::<Vec<_>>();
+
+        // Act
+        let rendered = render_stacks(&stacks);
+        let output = extract_stacks(rendered.lines());
+
+        // Assert
+        assert_eq!(output, stacks);
+        assert_eq!(
+            rendered.lines().last().unwrap(),
+            "  1      2      3      4      5      6      7      8      9      10     11     12  "
+        );
+    }
+
+    #[test]
+    fn test_reverse_from_stacks() {
+        // Arrange
+        let (stacks, commands) = extract_stacks_and_commands(INPUT).unwrap();
+        let final_9000 = simulate(INPUT, CrateMover9000).unwrap().stacks().to_vec();
+        let final_9001 = simulate(INPUT, CrateMover9001).unwrap().stacks().to_vec();
+
+        // Act
+        let output_9000 = reverse_from_stacks(final_9000, &commands, &CrateMover9000);
+        let output_9001 = reverse_from_stacks(final_9001, &commands, &CrateMover9001);
+
+        // Assert
+        assert_eq!(output_9000, Ok(stacks.clone()));
+        assert_eq!(output_9001, Ok(stacks));
+    }
+
+    #[test]
+    fn test_reverse_from_stacks_reports_impossible_commands() {
+        // Arrange
+        let (stacks, commands) = extract_stacks_and_commands(INPUT).unwrap();
+
+        // Act
+        let output = reverse_from_stacks(stacks, &commands, &CrateMover9000);
+
+        // Assert
+        assert_eq!(
+            output,
+            Err(CraneError::NotEnoughCratesToReverse {
+                index: 1,
+                command: (3, 0, 2),
+                available: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_reverse_from_tops() {
+        // Arrange
+        let (_, commands) = extract_stacks_and_commands(INPUT).unwrap();
+        let tops = "CMZ".chars().map(|top| Some(String::from(top)));
+
+        // Act
+        let output = reverse_from_tops(tops, &commands, &CrateMover9000).unwrap();
+        let filled = output
+            .iter()
+            .map(|stack| {
+                stack
+                    .iter()
+                    .map(|elem| elem.clone().unwrap_or_else(|| String::from("?")))
+                    .collect()
+            })
+            .collect();
+        let mut simulator = CraneSimulator::new(filled, CrateMover9000);
+        simulator.apply_all(commands).unwrap();
+
+        // Assert
+        assert_eq!(simulator.top_crates(), "CMZ");
+        assert_eq!(
+            output,
+            [
+                vec![Some(String::from("Z")), None],
+                vec![Some(String::from("M")), Some(String::from("C")), None],
+                vec![]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reverse_from_tops_with_empty_final_stack() {
+        // Arrange
+        let commands = [(1, 0, 1), (1, 0, 2)];
+        let tops = [None, Some(String::from("A")), Some(String::from("B"))];
+
+        // Act
+        let output = reverse_from_tops(tops, &commands, &CrateMover9000);
+
+        // Assert
+        assert_eq!(
+            output,
+            Ok(vec![
+                vec![Some(String::from("B")), Some(String::from("A"))],
+                vec![],
+                vec![]
+            ])
+        );
+    }
+
+    #[test]
+    fn test_malformed_commands_are_reported() {
+        // Arrange
+        const INPUT: &str = "
+[A] [B]
+ 1   2 
+
+move 1 from 1 to 2
+move 1 from 0 to 2
+";
+
+        // Act
+        let output = extract_stacks_and_commands(INPUT);
+
+        // Assert
+        assert_eq!(
+            output,
+            Err(CraneError::MalformedCommand {
+                index: 1,
+                text: String::from("move 1 from 0 to 2"),
+            })
+        );
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "command 2 (\"move 1 from 0 to 2\"): expected \"move <amount> from <stack> to <stack>\" with stacks numbered from 1"
+        );
+    }
 }
@@ -1,54 +1,293 @@
+use decoder::*;
+use detector::*;
+
 const INPUT: &str = include_str!("../input/day06.txt");
 
 #[cfg(not(tarpaulin))]
 fn main() {
-    println!("Part 1 => {}", part_1(INPUT));
-    println!("Part 2 => {}", part_2(INPUT));
+    [("Part 1", part_1(INPUT)), ("Part 2", part_2(INPUT))]
+        .into_iter()
+        .for_each(|(part, marker)| match marker {
+            Some(marker) => println!("{part} => {marker}"),
+            None => println!("{part} => no marker found"),
+        });
+    println!(
+        "Start-of-packet markers => {}",
+        Markers::new(INPUT.trim().as_bytes(), 4).count()
+    );
+    println!(
+        "Frames => {}",
+        Decoder::new(INPUT.trim().as_bytes(), Protocol::default()).count()
+    );
 }
 
-fn part_1(input: &str) -> usize {
+fn part_1(input: &str) -> Option<usize> {
     process_input(input, 4)
 }
 
-fn part_2(input: &str) -> usize {
+fn part_2(input: &str) -> Option<usize> {
     process_input(input, 14)
 }
 
-fn process_input(input: &str, window_size: usize) -> usize {
-    let bytes = input.trim().as_bytes();
-    bytes
-        .windows(window_size)
-        .enumerate()
-        .filter_map(|(index, window)| {
-            if bytes_in_slice_are_unique(window) {
-                Some(index + window_size)
-            } else {
-                None
+// None when the input has no marker.
+fn process_input(input: &str, window_size: usize) -> Option<usize> {
+    first_marker(input.trim().as_bytes(), window_size).expect("reading from memory can't fail")
+}
+
+mod detector {
+    use std::collections::VecDeque;
+    use std::io::{self, BufReader, Bytes, Read};
+
+    // a window of bytes is a marker when it's full and contains at least min_distinct distinct
+    // bytes. The puzzle's markers are the special case where every byte is distinct.
+    #[derive(Clone, Copy, Debug)]
+    pub struct MarkerRule {
+        pub window_size: usize,
+        pub min_distinct: usize,
+    }
+
+    impl MarkerRule {
+        pub fn all_distinct(window_size: usize) -> Self {
+            Self {
+                window_size,
+                min_distinct: window_size,
             }
-        })
-        .next()
-        .unwrap()
+        }
+    }
+
+    // sliding window which, rather than comparing every pair of bytes, keeps a count of each
+    // byte value in the window and of how many distinct values there are, so each byte pushed
+    // is O(1) work.
+    pub struct Window {
+        rule: MarkerRule,
+        bytes: VecDeque<u8>,
+        counts: [usize; 256],
+        distinct: usize,
+    }
+
+    impl Window {
+        pub fn new(rule: MarkerRule) -> Self {
+            assert!(rule.window_size > 0, "window size must be non-zero");
+            Self {
+                rule,
+                bytes: VecDeque::with_capacity(rule.window_size),
+                counts: [0; 256],
+                distinct: 0,
+            }
+        }
+
+        // pushes a byte into the window, returning whether the window is now a marker.
+        pub fn push(&mut self, byte: u8) -> bool {
+            if self.bytes.len() == self.rule.window_size {
+                let removed = self.bytes.pop_front().unwrap() as usize;
+                self.counts[removed] -= 1;
+                if self.counts[removed] == 0 {
+                    self.distinct -= 1;
+                }
+            }
+            self.bytes.push_back(byte);
+            self.counts[byte as usize] += 1;
+            if self.counts[byte as usize] == 1 {
+                self.distinct += 1;
+            }
+            self.bytes.len() == self.rule.window_size && self.distinct >= self.rule.min_distinct
+        }
+
+        pub fn clear(&mut self) {
+            self.bytes.clear();
+            self.counts = [0; 256];
+            self.distinct = 0;
+        }
+    }
+
+    // the position (number of bytes read) at the end of the first window of distinct bytes,
+    // or None if the stream ends without one.
+    pub fn first_marker(reader: impl Read, window_size: usize) -> io::Result<Option<usize>> {
+        Markers::new(reader, window_size).next().transpose()
+    }
+
+    // streams the positions at the end of every window matching a marker rule.
+    pub struct Markers<R> {
+        bytes: Bytes<BufReader<R>>,
+        window: Window,
+        position: usize,
+    }
+
+    impl<R: Read> Markers<R> {
+        pub fn new(reader: R, window_size: usize) -> Self {
+            Self::with_rule(reader, MarkerRule::all_distinct(window_size))
+        }
+
+        pub fn with_rule(reader: R, rule: MarkerRule) -> Self {
+            Self {
+                bytes: BufReader::new(reader).bytes(),
+                window: Window::new(rule),
+                position: 0,
+            }
+        }
+    }
+
+    impl<R: Read> Iterator for Markers<R> {
+        type Item = io::Result<usize>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                let byte = match self.bytes.next()? {
+                    Ok(byte) => byte,
+                    Err(error) => return Some(Err(error)),
+                };
+                self.position += 1;
+                if self.window.push(byte) {
+                    return Some(Ok(self.position));
+                }
+            }
+        }
+    }
 }
 
-fn bytes_in_slice_are_unique(slice: &[u8]) -> bool {
-    (0..slice.len() - 1).all(|first_index| {
-        let first_byte = slice[first_index];
-        !(first_index + 1..slice.len()).any(|second_index| {
-            let second_byte = slice[second_index];
-            first_byte == second_byte
-        })
-    })
+mod decoder {
+    use super::detector::{MarkerRule, Window};
+    use core::ops::Range;
+
+    #[derive(Clone, Copy, Debug)]
+    pub struct Protocol {
+        pub packet: MarkerRule,
+        pub message: MarkerRule,
+        pub messages_per_frame: usize,
+    }
+
+    impl Default for Protocol {
+        fn default() -> Self {
+            Self {
+                packet: MarkerRule::all_distinct(4),
+                message: MarkerRule::all_distinct(14),
+                messages_per_frame: 1,
+            }
+        }
+    }
+
+    // a frame runs from the start of its packet marker up to the start of the next frame's
+    // packet marker (or the end of the datastream).
+    #[derive(Debug, PartialEq)]
+    pub struct Frame<'a> {
+        pub range: Range<usize>,
+        pub packet_marker: Range<usize>,
+        pub messages: Vec<Message<'a>>,
+    }
+
+    // a message's payload runs from the end of its marker up to the start of the next marker.
+    #[derive(Debug, PartialEq)]
+    pub struct Message<'a> {
+        pub marker: Range<usize>,
+        pub payload: &'a [u8],
+    }
+
+    // segments a datastream into frames. Bytes before the first packet marker are skipped, then
+    // each frame is a packet marker followed by up to messages_per_frame message markers. Only one
+    // rule is looked for at a time (message markers until the frame is full, then the next packet
+    // marker) and the window starts afresh after each marker, so markers never overlap.
+    pub struct Decoder<'a> {
+        data: &'a [u8],
+        position: usize,
+        protocol: Protocol,
+        packet_window: Window,
+        message_window: Window,
+        current: Option<Frame<'a>>,
+    }
+
+    impl<'a> Decoder<'a> {
+        pub fn new(data: &'a [u8], protocol: Protocol) -> Self {
+            Self {
+                data,
+                position: 0,
+                protocol,
+                packet_window: Window::new(protocol.packet),
+                message_window: Window::new(protocol.message),
+                current: None,
+            }
+        }
+
+        fn is_seeking_message(&self) -> bool {
+            matches!(&self.current, Some(frame) if frame.messages.len() < self.protocol.messages_per_frame)
+        }
+
+        // finishes the payload of the last message in the current frame, if any.
+        fn close_message(&mut self, end: usize) {
+            if let Some(message) = self
+                .current
+                .as_mut()
+                .and_then(|frame| frame.messages.last_mut())
+            {
+                message.payload = &self.data[message.marker.end..end];
+            }
+        }
+    }
+
+    impl<'a> Iterator for Decoder<'a> {
+        type Item = Frame<'a>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            while let Some(byte) = self.data.get(self.position).copied() {
+                self.position += 1;
+                if self.is_seeking_message() {
+                    if self.message_window.push(byte) {
+                        let marker =
+                            self.position - self.protocol.message.window_size..self.position;
+                        self.close_message(marker.start);
+                        self.message_window.clear();
+                        if let Some(frame) = self.current.as_mut() {
+                            frame.messages.push(Message {
+                                marker,
+                                payload: &[],
+                            });
+                        }
+                    }
+                } else if self.packet_window.push(byte) {
+                    let marker = self.position - self.protocol.packet.window_size..self.position;
+                    self.close_message(marker.start);
+                    self.packet_window.clear();
+                    let next = Frame {
+                        range: marker.start..marker.start,
+                        packet_marker: marker.clone(),
+                        messages: Vec::new(),
+                    };
+                    if let Some(mut frame) = self.current.replace(next) {
+                        frame.range.end = marker.start;
+                        return Some(frame);
+                    }
+                }
+            }
+
+            // end of the datastream closes off whatever frame we were in.
+            self.close_message(self.data.len());
+            self.current.take().map(|mut frame| {
+                frame.range.end = self.data.len();
+                frame
+            })
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // the original quadratic check, kept as a reference for the streaming detector.
+    fn bytes_in_slice_are_unique(slice: &[u8]) -> bool {
+        (0..slice.len() - 1).all(|first_index| {
+            let first_byte = slice[first_index];
+            !(first_index + 1..slice.len()).any(|second_index| {
+                let second_byte = slice[second_index];
+                first_byte == second_byte
+            })
+        })
+    }
+
     #[test]
     fn test_part_1_1() {
         // Arrange
         const INPUT: &str = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
-        const EXPECTED: usize = 7;
+        const EXPECTED: Option<usize> = Some(7);
 
         // Act
         let output = part_1(INPUT);
@@ -61,7 +300,7 @@ mod tests {
     fn test_part_1_2() {
         // Arrange
         const INPUT: &str = "bvwbjplbgvbhsrlpgdmjqwftvncz";
-        const EXPECTED: usize = 5;
+        const EXPECTED: Option<usize> = Some(5);
 
         // Act
         let output = part_1(INPUT);
@@ -74,7 +313,7 @@ mod tests {
     fn test_part_1_3() {
         // Arrange
         const INPUT: &str = "nppdvjthqldpwncqszvftbrmjlhg";
-        const EXPECTED: usize = 6;
+        const EXPECTED: Option<usize> = Some(6);
 
         // Act
         let output = part_1(INPUT);
@@ -87,7 +326,7 @@ mod tests {
     fn test_part_2_1() {
         // Arrange
         const INPUT: &str = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
-        const EXPECTED: usize = 19;
+        const EXPECTED: Option<usize> = Some(19);
 
         // Act
         let output = part_2(INPUT);
@@ -100,7 +339,7 @@ mod tests {
     fn test_part_2_2() {
         // Arrange
         const INPUT: &str = "bvwbjplbgvbhsrlpgdmjqwftvncz";
-        const EXPECTED: usize = 23;
+        const EXPECTED: Option<usize> = Some(23);
 
         // Act
         let output = part_2(INPUT);
@@ -113,7 +352,7 @@ mod tests {
     fn test_part_3_3() {
         // Arrange
         const INPUT: &str = "nppdvjthqldpwncqszvftbrmjlhg";
-        const EXPECTED: usize = 23;
+        const EXPECTED: Option<usize> = Some(23);
 
         // Act
         let output = part_2(INPUT);
@@ -121,4 +360,131 @@ mod tests {
         // Assert
         assert_eq!(output, EXPECTED);
     }
+
+    #[test]
+    fn test_part_1_no_marker() {
+        // Arrange
+        const INPUT: &str = "abcabcabc";
+
+        // Act
+        let output = part_1(INPUT);
+
+        // Assert
+        assert_eq!(output, None);
+    }
+
+    #[test]
+    fn test_first_marker_none() {
+        // Arrange
+        const INPUT: &str = "abcabcabc";
+
+        // Act
+        let output = first_marker(INPUT.as_bytes(), 4).unwrap();
+
+        // Assert
+        assert_eq!(output, None);
+    }
+
+    #[test]
+    fn test_markers_all_positions() {
+        // Arrange
+        const INPUT: &str = "aabcdbbcda";
+        const EXPECTED: [usize; 2] = [5, 10];
+
+        // Act
+        let output = Markers::new(INPUT.as_bytes(), 4)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_markers_match_reference_on_large_windows() {
+        // Arrange
+        let bytes = (0..5_000_u32)
+            .map(|index| (index.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect::<Vec<_>>();
+
+        // Act / Assert
+        [1, 2, 50, 200, 256].into_iter().for_each(|window_size| {
+            let expected = bytes
+                .windows(window_size)
+                .enumerate()
+                .filter(|(_, window)| window_size == 1 || bytes_in_slice_are_unique(window))
+                .map(|(index, _)| index + window_size)
+                .collect::<Vec<_>>();
+            let output = Markers::new(bytes.as_slice(), window_size)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(output, expected);
+        });
+    }
+
+    #[test]
+    fn test_markers_with_distinct_threshold() {
+        // Arrange
+        const INPUT: &str = "aaabaaacaa";
+        const EXPECTED: [usize; 6] = [4, 5, 6, 8, 9, 10];
+        let rule = MarkerRule {
+            window_size: 3,
+            min_distinct: 2,
+        };
+
+        // Act
+        let output = Markers::with_rule(INPUT.as_bytes(), rule)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_decoder_frames() {
+        // Arrange
+        const INPUT: &[u8] = b"xxxabcdyyzzzklmqqqaaaefghabcqqq";
+        let protocol = Protocol {
+            packet: MarkerRule::all_distinct(4),
+            message: MarkerRule::all_distinct(3),
+            messages_per_frame: 2,
+        };
+        let expected = vec![
+            Frame {
+                range: 2..23,
+                packet_marker: 2..6,
+                messages: vec![
+                    Message {
+                        marker: 11..14,
+                        payload: b"mqqqaa",
+                    },
+                    Message {
+                        marker: 20..23,
+                        payload: b"",
+                    },
+                ],
+            },
+            Frame {
+                range: 23..31,
+                packet_marker: 23..27,
+                messages: vec![],
+            },
+        ];
+
+        // Act
+        let output = Decoder::new(INPUT, protocol).collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_decoder_without_packet_marker() {
+        // Act
+        let output = Decoder::new(b"aaaaaaa", Protocol::default()).next();
+
+        // Assert
+        assert_eq!(output, None);
+    }
 }
@@ -1,182 +1,668 @@
-use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
-use std::rc::{Rc, Weak};
+use filesystem::*;
+use reports::*;
+use transcript::*;
 
 const INPUT: &str = include_str!("../input/day07.txt");
 
+const DISK_SIZE: u64 = 70_000_000;
+const REQUIRED_SIZE: u64 = 30_000_000;
+
+const USAGE: &str =
+    "usage: day07 [<directory> [solve [<disk size> <required size>] | du | tree | transcript]]";
+
 #[cfg(not(tarpaulin))]
 fn main() {
-    println!("Part 1 => {}", part_1(INPUT));
-    println!("Part 2 => {}", part_2(INPUT));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() {
+        println!("Part 1 => {}", part_1(INPUT));
+        match part_2(INPUT) {
+            Some(size) => println!("Part 2 => {size}"),
+            None => println!("Part 2 => no single directory frees enough space"),
+        }
+        return;
+    }
+    if let Err(error) = run(&args) {
+        eprintln!("{error}\n{USAGE}");
+        std::process::exit(2);
+    }
 }
 
-type EntryPointer = Rc<RefCell<Entry>>;
-type EntryWeakPointer = Weak<RefCell<Entry>>;
-
-#[derive(Debug)]
-struct Entry {
-    parent: Option<EntryWeakPointer>,
-    entry_type: EntryType,
+// reports on a directory on disk, an error being a message to print along with the usage.
+#[cfg(not(tarpaulin))]
+fn run(args: &[String]) -> Result<(), String> {
+    let path = &args[0];
+    let file_system = FileSystem::from_directory(std::path::Path::new(path))
+        .map_err(|error| format!("failed to read {path}: {error}"))?;
+    match args.get(1).map(|arg| arg.as_str()) {
+        None | Some("solve") => {
+            let (disk_size, required_size) = match args.get(2..) {
+                None | Some([]) => (DISK_SIZE, REQUIRED_SIZE),
+                Some([disk_size, required_size]) => {
+                    (parse_size(disk_size)?, parse_size(required_size)?)
+                }
+                Some(_) => return Err(String::from("expected a disk size and a required size")),
+            };
+            println!(
+                "Part 1 => {}",
+                sum_of_directories_up_to(&file_system, 100_000)
+            );
+            match smallest_directory_to_free(&file_system, disk_size, required_size) {
+                Some(size) => println!("Part 2 => {size}"),
+                None => println!("Part 2 => no single directory frees enough space"),
+            }
+        }
+        Some("du") => print!("{}", du_report(&file_system)),
+        Some("tree") => print!("{}", tree_report(&file_system)),
+        Some("transcript") => print!("{}", to_transcript(&file_system)),
+        Some(other) => return Err(format!("unknown report {other:?}")),
+    }
+    Ok(())
 }
 
-#[derive(Debug)]
-enum EntryType {
-    Directory {
-        children: HashMap<String, EntryPointer>,
-    },
-    File {
-        size: u32,
-    },
+#[cfg(not(tarpaulin))]
+fn parse_size(size: &str) -> Result<u64, String> {
+    size.parse()
+        .map_err(|error| format!("invalid size {size:?}: {error}"))
 }
 
-impl Entry {
-    fn for_each_directory(&self, func: impl Fn(&Self) + Copy) {
-        if let EntryType::Directory { children } = &self.entry_type {
-            func(self);
-            children
-                .values()
-                .for_each(|child| child.borrow().for_each_directory(func));
+mod filesystem {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    pub type NodeId = usize;
+
+    #[derive(Debug)]
+    pub enum NodeKind {
+        Directory { children: BTreeMap<String, NodeId> },
+        File,
+    }
+
+    // a file or directory, the size of a directory being the cumulative size of everything in it.
+    #[derive(Debug)]
+    pub struct Node {
+        pub name: String,
+        pub path: String,
+        pub parent: Option<NodeId>,
+        pub kind: NodeKind,
+        pub size: u64,
+    }
+
+    impl Node {
+        pub fn is_directory(&self) -> bool {
+            matches!(self.kind, NodeKind::Directory { .. })
         }
     }
 
-    fn size(&self) -> u32 {
-        match &self.entry_type {
-            EntryType::Directory { children } => {
-                children.values().map(|child| child.borrow().size()).sum()
+    // index-based tree, nodes refer to each other by their index into the arena so there's no
+    // need for shared ownership. Directory sizes are kept up to date as files are added, so
+    // asking for the size of a directory is O(1).
+    #[derive(Debug)]
+    pub struct FileSystem {
+        nodes: Vec<Node>,
+    }
+
+    impl Default for FileSystem {
+        fn default() -> Self {
+            Self {
+                nodes: vec![Node {
+                    name: String::new(),
+                    path: String::from("/"),
+                    parent: None,
+                    kind: NodeKind::Directory {
+                        children: BTreeMap::new(),
+                    },
+                    size: 0,
+                }],
             }
-            EntryType::File { size } => *size,
         }
     }
-}
 
-fn part_1(input: &str) -> u32 {
-    let dir_structure = calculate_dir_structure(input);
-    let total_size = Cell::new(0);
-    dir_structure.borrow().for_each_directory(|entry| {
-        let size = entry.size();
-        if size <= 100_000 {
-            total_size.replace(total_size.get() + size);
+    impl FileSystem {
+        // builds the tree from a directory on disk, using each file's length as its size.
+        // Symbolic links aren't followed, they count as files of their own size.
+        pub fn from_directory(path: &Path) -> io::Result<Self> {
+            let mut file_system = Self::default();
+            let root = file_system.root();
+            file_system.add_directory_contents(root, path)?;
+            Ok(file_system)
         }
-    });
-    total_size.get()
-}
 
-fn part_2(input: &str) -> u32 {
-    let dir_structure = calculate_dir_structure(input);
-    let total_used = dir_structure.borrow().size();
-    let total_unused = 70_000_000 - total_used;
-    let required_to_free = 30_000_000 - total_unused;
-    let minimal_size = Cell::new(u32::MAX);
-    dir_structure.borrow().for_each_directory(|entry| {
-        let size = entry.size();
-        if size >= required_to_free {
-            minimal_size.replace(std::cmp::min(minimal_size.get(), size));
-        }
-    });
-    minimal_size.get()
-}
+        pub fn root(&self) -> NodeId {
+            0
+        }
 
-fn calculate_dir_structure(input: &str) -> EntryPointer {
-    // hold onto the root directory for navigation straight back to root and for returning later.
-    let root = create_new_directory(None);
-
-    // starting off at the root as current.
-    let mut current = Rc::clone(&root);
-
-    // process the commands line by line.
-    input.trim().lines().for_each(|line| match line.trim() {
-        "$ cd /" => current = Rc::clone(&root),
-        "$ ls" => {} // ls results in the output in the following lines, so nothing to do
-        "$ cd .." => {
-            let new_current = current.borrow().parent.as_ref().unwrap().upgrade().unwrap();
-            current = new_current;
-        }
-        line => {
-            // all of the remaining line types will assume that the current
-            // entry is a Directory as it'll need access to the children
-            let replacement = match &mut current.borrow_mut().entry_type {
-                EntryType::Directory { children } => {
-                    if let Some(line) = line.strip_prefix("dir ") {
-                        children.insert(
-                            line.to_string(),
-                            create_new_directory(Some(Rc::downgrade(&current))),
-                        );
-                        None
-                    } else if let Some(line) = line.strip_prefix("$ cd ") {
-                        Some(Rc::clone(&children[line]))
-                    } else {
-                        let mut file_parts = line.split_whitespace();
-                        let size = file_parts.next().unwrap().parse::<u32>().unwrap();
-                        let name = file_parts.next().unwrap();
-                        children.insert(
-                            name.to_string(),
-                            create_new_file(Some(Rc::downgrade(&current)), size),
-                        );
-                        None
-                    }
+        pub fn node(&self, id: NodeId) -> &Node {
+            &self.nodes[id]
+        }
+
+        pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+            self.nodes[id].parent
+        }
+
+        pub fn child(&self, id: NodeId, name: &str) -> Option<NodeId> {
+            match &self.nodes[id].kind {
+                NodeKind::Directory { children } => children.get(name).copied(),
+                NodeKind::File => None,
+            }
+        }
+
+        pub fn children(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+            let children = match &self.nodes[id].kind {
+                NodeKind::Directory { children } => Some(children.values().copied()),
+                NodeKind::File => None,
+            };
+            children.into_iter().flatten()
+        }
+
+        // finds a node by its absolute path, e.g. "/a/e".
+        #[cfg(test)]
+        pub fn lookup(&self, path: &str) -> Option<NodeId> {
+            path.split('/')
+                .filter(|name| !name.is_empty())
+                .try_fold(self.root(), |id, name| self.child(id, name))
+        }
+
+        // adds a directory to the given one, or returns the existing directory of that name. None,
+        // leaving the tree unchanged, if there's already a file of that name.
+        pub fn add_directory(&mut self, parent: NodeId, name: &str) -> Option<NodeId> {
+            match self.child(parent, name) {
+                Some(existing) if self.nodes[existing].is_directory() => Some(existing),
+                Some(_) => None,
+                None => Some(self.insert_node(
+                    parent,
+                    name,
+                    NodeKind::Directory {
+                        children: BTreeMap::new(),
+                    },
+                )),
+            }
+        }
+
+        // adds a file to the given directory, or updates the size of the existing file of that name.
+        // None, leaving the tree unchanged, if there's already a directory of that name.
+        pub fn add_file(&mut self, parent: NodeId, name: &str, size: u64) -> Option<NodeId> {
+            let id = match self.child(parent, name) {
+                Some(existing) if self.nodes[existing].is_directory() => return None,
+                Some(existing) => existing,
+                None => self.insert_node(parent, name, NodeKind::File),
+            };
+            let previous_size = self.nodes[id].size;
+            let mut current = Some(id);
+            while let Some(index) = current {
+                let node = &mut self.nodes[index];
+                node.size = node.size - previous_size + size;
+                current = node.parent;
+            }
+            Some(id)
+        }
+
+        pub fn iter_pre_order(&self) -> impl Iterator<Item = NodeId> + '_ {
+            let mut stack = vec![self.root()];
+            std::iter::from_fn(move || {
+                let id = stack.pop()?;
+                let children_start = stack.len();
+                stack.extend(self.children(id));
+                stack[children_start..].reverse();
+                Some(id)
+            })
+        }
+
+        pub fn iter_post_order(&self) -> impl Iterator<Item = NodeId> + '_ {
+            // a pre-order which visits children last to first, reversed, is a post-order.
+            let mut stack = vec![self.root()];
+            let mut reversed = Vec::with_capacity(self.nodes.len());
+            while let Some(id) = stack.pop() {
+                reversed.push(id);
+                stack.extend(self.children(id));
+            }
+            reversed.into_iter().rev()
+        }
+
+        pub fn iter_directories(&self) -> impl Iterator<Item = &Node> + '_ {
+            self.iter_pre_order()
+                .map(|id| &self.nodes[id])
+                .filter(|node| node.is_directory())
+        }
+
+        fn add_directory_contents(&mut self, directory: NodeId, path: &Path) -> io::Result<()> {
+            fs::read_dir(path)?.try_for_each(|entry| {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let metadata = entry.metadata()?;
+                // names within a directory on disk are unique, so adding them can't conflict.
+                if metadata.is_dir() {
+                    let child = self.add_directory(directory, &name).unwrap();
+                    self.add_directory_contents(child, &entry.path())
+                } else {
+                    self.add_file(directory, &name, metadata.len()).unwrap();
+                    Ok(())
                 }
-                _ => unimplemented!(),
+            })
+        }
+
+        fn insert_node(&mut self, parent: NodeId, name: &str, kind: NodeKind) -> NodeId {
+            let id = self.nodes.len();
+            let parent_path = &self.nodes[parent].path;
+            let path = if parent_path == "/" {
+                format!("/{name}")
+            } else {
+                format!("{parent_path}/{name}")
             };
-            if let Some(replacement) = replacement {
-                current = replacement;
+            self.nodes.push(Node {
+                name: name.to_string(),
+                path,
+                parent: Some(parent),
+                kind,
+                size: 0,
+            });
+            if let NodeKind::Directory { children } = &mut self.nodes[parent].kind {
+                children.insert(name.to_string(), id);
             }
+            id
         }
-    });
+    }
+}
 
-    // return root.
-    root
+fn part_1(input: &str) -> u64 {
+    sum_of_directories_up_to(&calculate_dir_structure(input), 100_000)
 }
 
-fn create_new_directory(parent: Option<EntryWeakPointer>) -> EntryPointer {
-    Rc::new(RefCell::new(Entry {
-        parent,
-        entry_type: EntryType::Directory {
-            children: HashMap::new(),
-        },
-    }))
+fn part_2(input: &str) -> Option<u64> {
+    smallest_directory_to_free(&calculate_dir_structure(input), DISK_SIZE, REQUIRED_SIZE)
 }
 
-fn create_new_file(parent: Option<EntryWeakPointer>, size: u32) -> EntryPointer {
-    Rc::new(RefCell::new(Entry {
-        parent,
-        entry_type: EntryType::File { size },
-    }))
+fn sum_of_directories_up_to(file_system: &FileSystem, threshold: u64) -> u64 {
+    file_system
+        .iter_directories()
+        .map(|directory| directory.size)
+        .filter(|size| *size <= threshold)
+        .sum()
+}
+
+// size of the smallest directory which, when deleted, leaves at least required_size unused on
+// a disk of disk_size. Zero if there's already enough space, None if no directory is big enough.
+fn smallest_directory_to_free(
+    file_system: &FileSystem,
+    disk_size: u64,
+    required_size: u64,
+) -> Option<u64> {
+    let total_used = file_system.node(file_system.root()).size;
+    let total_unused = disk_size.saturating_sub(total_used);
+    let required_to_free = required_size.saturating_sub(total_unused);
+    if required_to_free == 0 {
+        return Some(0);
+    }
+    file_system
+        .iter_directories()
+        .map(|directory| directory.size)
+        .filter(|size| *size >= required_to_free)
+        .min()
+}
+
+mod reports {
+    use super::filesystem::{FileSystem, NodeId};
+
+    // every file and directory with its size, children before their parents like `du -a`.
+    pub fn du_report(file_system: &FileSystem) -> String {
+        file_system
+            .iter_post_order()
+            .map(|id| {
+                let node = file_system.node(id);
+                format!("{}\t{}\n", node.size, node.path)
+            })
+            .collect()
+    }
+
+    // the hierarchy drawn like `tree`, with the size of each entry.
+    pub fn tree_report(file_system: &FileSystem) -> String {
+        let root = file_system.root();
+        let mut report = format!("[{}]  /\n", file_system.node(root).size);
+        write_tree_children(file_system, root, "", &mut report);
+        report
+    }
+
+    fn write_tree_children(
+        file_system: &FileSystem,
+        id: NodeId,
+        prefix: &str,
+        report: &mut String,
+    ) {
+        let children = file_system.children(id).collect::<Vec<_>>();
+        children.iter().enumerate().for_each(|(index, child)| {
+            let is_last = index == children.len() - 1;
+            let node = file_system.node(*child);
+            let (branch, indent) = if is_last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            let suffix = if node.is_directory() { "/" } else { "" };
+            report.push_str(&format!(
+                "{prefix}{branch}[{}]  {}{suffix}\n",
+                node.size, node.name
+            ));
+            write_tree_children(file_system, *child, &format!("{prefix}{indent}"), report);
+        });
+    }
+
+    // a synthetic `$ cd`/`$ ls` transcript which parses back into the same tree.
+    pub fn to_transcript(file_system: &FileSystem) -> String {
+        let mut transcript = String::from("$ cd /\n");
+        write_listing(file_system, file_system.root(), &mut transcript);
+        transcript
+    }
+
+    fn write_listing(file_system: &FileSystem, id: NodeId, transcript: &mut String) {
+        transcript.push_str("$ ls\n");
+        file_system.children(id).for_each(|child| {
+            let node = file_system.node(child);
+            if node.is_directory() {
+                transcript.push_str(&format!("dir {}\n", node.name));
+            } else {
+                transcript.push_str(&format!("{} {}\n", node.size, node.name));
+            }
+        });
+        file_system
+            .children(id)
+            .filter(|child| file_system.node(*child).is_directory())
+            .for_each(|child| {
+                transcript.push_str(&format!("$ cd {}\n", file_system.node(child).name));
+                write_listing(file_system, child, transcript);
+                transcript.push_str("$ cd ..\n");
+            });
+    }
+}
+
+fn calculate_dir_structure(input: &str) -> FileSystem {
+    match parse_transcript(input) {
+        (file_system, diagnostics) if diagnostics.is_empty() => file_system,
+        (_, diagnostics) => panic!(
+            "invalid transcript:\n{}",
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    }
+}
+
+mod transcript {
+    use super::filesystem::{FileSystem, NodeId};
+    use core::fmt::{self, Display, Formatter};
+    use std::collections::{BTreeSet, HashMap};
+
+    #[derive(Debug, PartialEq)]
+    pub enum DiagnosticKind {
+        UnknownCommand(String),
+        MalformedEntry(String),
+        OutputWithoutListing,
+        AboveRoot,
+        NotADirectory {
+            path: String,
+        },
+        UnlistedDirectory {
+            path: String,
+        },
+        ConflictingSize {
+            path: String,
+            previous: u64,
+            size: u64,
+        },
+        ConflictingType {
+            path: String,
+        },
+        MissingFromRelisting {
+            path: String,
+        },
+    }
+
+    // a problem found in a transcript, line numbers are 1-based.
+    #[derive(Debug, PartialEq)]
+    pub struct Diagnostic {
+        pub line: usize,
+        pub kind: DiagnosticKind,
+    }
+
+    impl Display for Diagnostic {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            write!(f, "line {}: ", self.line)?;
+            match &self.kind {
+                DiagnosticKind::UnknownCommand(command) => write!(f, "unknown command {command:?}"),
+                DiagnosticKind::MalformedEntry(entry) => write!(f, "malformed ls output {entry:?}"),
+                DiagnosticKind::OutputWithoutListing => write!(f, "output without a preceding ls"),
+                DiagnosticKind::AboveRoot => write!(f, "cd .. from the root directory"),
+                DiagnosticKind::NotADirectory { path } => {
+                    write!(f, "cd into {path} which is a file")
+                }
+                DiagnosticKind::UnlistedDirectory { path } => {
+                    write!(f, "cd into {path} which hasn't been listed")
+                }
+                DiagnosticKind::ConflictingSize {
+                    path,
+                    previous,
+                    size,
+                } => write!(
+                    f,
+                    "{path} listed with size {size} but was previously {previous}"
+                ),
+                DiagnosticKind::ConflictingType { path } => {
+                    write!(f, "{path} listed as both a file and a directory")
+                }
+                DiagnosticKind::MissingFromRelisting { path } => {
+                    write!(f, "{path} missing when its directory was listed again")
+                }
+            }
+        }
+    }
+
+    // the directory currently being listed, with the line of the ls and the entries seen so far.
+    struct Listing {
+        directory: NodeId,
+        line: usize,
+        seen: BTreeSet<String>,
+    }
+
+    // builds the file system from a transcript, carrying on past any problems (with the first
+    // listing of an entry winning over later conflicting ones) and reporting each as a diagnostic.
+    pub fn parse_transcript(input: &str) -> (FileSystem, Vec<Diagnostic>) {
+        let mut file_system = FileSystem::default();
+        let mut current = file_system.root();
+        let mut listing: Option<Listing> = None;
+        let mut listed: HashMap<NodeId, BTreeSet<String>> = HashMap::new();
+        let mut diagnostics = Vec::new();
+
+        // only line endings are stripped, names can start or end with spaces.
+        input.lines().enumerate().for_each(|(index, line)| {
+            let line_number = index + 1;
+            let command = line.strip_prefix("$ ");
+            if command.is_some() {
+                if let Some(finished) = listing.take() {
+                    finish_listing(&file_system, finished, &mut listed, &mut diagnostics);
+                }
+            }
+            let mut report = |kind| {
+                diagnostics.push(Diagnostic {
+                    line: line_number,
+                    kind,
+                })
+            };
+            if let Some(command) = command {
+                if command == "ls" {
+                    listing = Some(Listing {
+                        directory: current,
+                        line: line_number,
+                        seen: BTreeSet::new(),
+                    });
+                } else if let Some(path) = command.strip_prefix("cd ") {
+                    current = change_directory(&mut file_system, current, path, &mut report);
+                } else {
+                    report(DiagnosticKind::UnknownCommand(command.to_string()));
+                }
+            } else if let Some(listing) = listing.as_mut() {
+                add_entry(&mut file_system, listing, line, &mut report);
+            } else {
+                report(DiagnosticKind::OutputWithoutListing);
+            }
+        });
+        if let Some(finished) = listing.take() {
+            finish_listing(&file_system, finished, &mut listed, &mut diagnostics);
+        }
+        // entries missing from a re-listing are only known once it ends, so put them back in
+        // line order (the sort is stable, keeping the order of diagnostics on the same line).
+        diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+
+        (file_system, diagnostics)
+    }
+
+    // follows an absolute or relative path, returning the new current directory.
+    fn change_directory(
+        file_system: &mut FileSystem,
+        current: NodeId,
+        path: &str,
+        report: &mut impl FnMut(DiagnosticKind),
+    ) -> NodeId {
+        let start = if path.starts_with('/') {
+            file_system.root()
+        } else {
+            current
+        };
+        path.split('/')
+            .filter(|name| !name.is_empty() && *name != ".")
+            .fold(start, |directory, name| {
+                if name == ".." {
+                    file_system.parent(directory).unwrap_or_else(|| {
+                        report(DiagnosticKind::AboveRoot);
+                        directory
+                    })
+                } else {
+                    match file_system.child(directory, name) {
+                        Some(child) if file_system.node(child).is_directory() => child,
+                        Some(child) => {
+                            report(DiagnosticKind::NotADirectory {
+                                path: file_system.node(child).path.clone(),
+                            });
+                            directory
+                        }
+                        None => {
+                            // carry on as if it had been listed.
+                            let child = file_system.add_directory(directory, name).unwrap();
+                            report(DiagnosticKind::UnlistedDirectory {
+                                path: file_system.node(child).path.clone(),
+                            });
+                            child
+                        }
+                    }
+                }
+            })
+    }
+
+    // adds a line of ls output ("dir <name>" or "<size> <name>", names may contain spaces).
+    fn add_entry(
+        file_system: &mut FileSystem,
+        listing: &mut Listing,
+        line: &str,
+        report: &mut impl FnMut(DiagnosticKind),
+    ) {
+        let (name, size) = if let Some(name) = line.strip_prefix("dir ") {
+            (name, None)
+        } else if let Some((size, name)) = line
+            .split_once(' ')
+            .and_then(|(size, name)| Some((size.parse::<u64>().ok()?, name)))
+        {
+            (name, Some(size))
+        } else {
+            report(DiagnosticKind::MalformedEntry(line.to_string()));
+            return;
+        };
+        if name.is_empty() || name.contains('/') {
+            report(DiagnosticKind::MalformedEntry(line.to_string()));
+            return;
+        }
+        listing.seen.insert(name.to_string());
+        let existing = file_system
+            .child(listing.directory, name)
+            .map(|id| file_system.node(id));
+        match (existing, size) {
+            (None, None) => {
+                file_system.add_directory(listing.directory, name);
+            }
+            (None, Some(size)) => {
+                file_system.add_file(listing.directory, name, size);
+            }
+            (Some(node), None) if node.is_directory() => {}
+            (Some(node), Some(size)) if !node.is_directory() => {
+                if node.size != size {
+                    report(DiagnosticKind::ConflictingSize {
+                        path: node.path.clone(),
+                        previous: node.size,
+                        size,
+                    });
+                }
+            }
+            (Some(node), _) => report(DiagnosticKind::ConflictingType {
+                path: node.path.clone(),
+            }),
+        }
+    }
+
+    // checks a re-listing of a directory has everything the earlier listings did.
+    fn finish_listing(
+        file_system: &FileSystem,
+        listing: Listing,
+        listed: &mut HashMap<NodeId, BTreeSet<String>>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let previously_seen = listed.entry(listing.directory).or_default();
+        diagnostics.extend(previously_seen.difference(&listing.seen).map(|name| {
+            let child = file_system.child(listing.directory, name).unwrap();
+            Diagnostic {
+                line: listing.line,
+                kind: DiagnosticKind::MissingFromRelisting {
+                    path: file_system.node(child).path.clone(),
+                },
+            }
+        }));
+        previously_seen.extend(listing.seen);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const INPUT: &str = "
-    $ cd /
-    $ ls
-    dir a
-    14848514 b.txt
-    8504156 c.dat
-    dir d
-    $ cd a
-    $ ls
-    dir e
-    29116 f
-    2557 g
-    62596 h.lst
-    $ cd e
-    $ ls
-    584 i
-    $ cd ..
-    $ cd ..
-    $ cd d
-    $ ls
-    4060174 j
-    8033020 d.log
-    5626152 d.ext
-    7214296 k
-    ";
+    const INPUT: &str = "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+";
 
     #[test]
     fn test_part_1() {
         // Arrange
-        const EXPECTED: u32 = 95437;
+        const EXPECTED: u64 = 95437;
 
         // Act
         let output = part_1(INPUT);
@@ -188,7 +674,7 @@ mod tests {
     #[test]
     fn test_part_2() {
         // Arrange
-        const EXPECTED: u32 = 24933642;
+        const EXPECTED: Option<u64> = Some(24933642);
 
         // Act
         let output = part_2(INPUT);
@@ -196,4 +682,329 @@ mod tests {
         // Assert
         assert_eq!(output, EXPECTED);
     }
+
+    #[test]
+    fn test_directory_sizes_and_lookup() {
+        // Arrange
+        let file_system = calculate_dir_structure(INPUT);
+
+        // Act
+        let e = file_system.lookup("/a/e").unwrap();
+        let a = file_system.lookup("/a").unwrap();
+        let missing = file_system.lookup("/a/x");
+
+        // Assert
+        assert_eq!(file_system.node(e).size, 584);
+        assert_eq!(file_system.node(e).path, "/a/e");
+        assert_eq!(file_system.node(a).size, 94853);
+        assert_eq!(file_system.node(file_system.root()).size, 48381165);
+        assert_eq!(missing, None);
+    }
+
+    #[test]
+    fn test_traversal_orders() {
+        // Arrange
+        let file_system = calculate_dir_structure(INPUT);
+        let directory_paths = |ids: &mut dyn Iterator<Item = NodeId>| {
+            ids.map(|id| file_system.node(id))
+                .filter(|node| node.is_directory())
+                .map(|node| node.path.clone())
+                .collect::<Vec<_>>()
+        };
+
+        // Act
+        let pre_order = directory_paths(&mut file_system.iter_pre_order());
+        let post_order = directory_paths(&mut file_system.iter_post_order());
+
+        // Assert
+        assert_eq!(pre_order, ["/", "/a", "/a/e", "/d"]);
+        assert_eq!(post_order, ["/a/e", "/a", "/d", "/"]);
+    }
+
+    #[test]
+    fn test_relisting_file_does_not_double_count() {
+        // Arrange
+        let mut file_system = FileSystem::default();
+        let root = file_system.root();
+        let a = file_system.add_directory(root, "a").unwrap();
+
+        // Act
+        file_system.add_file(a, "b", 10);
+        file_system.add_file(a, "b", 15);
+        file_system.add_directory(root, "a");
+
+        // Assert
+        assert_eq!(file_system.node(root).size, 15);
+        assert_eq!(file_system.children(root).count(), 1);
+    }
+
+    #[test]
+    fn test_adding_entry_over_other_kind_is_ignored() {
+        // Arrange
+        let mut file_system = FileSystem::default();
+        let root = file_system.root();
+        let a = file_system.add_directory(root, "a").unwrap();
+        file_system.add_file(a, "b", 10);
+        file_system.add_file(root, "c", 5);
+
+        // Act
+        let file_over_directory = file_system.add_file(root, "a", 20);
+        let directory_over_file = file_system.add_directory(root, "c");
+
+        // Assert
+        assert_eq!((file_over_directory, directory_over_file), (None, None));
+        assert!(file_system.node(a).is_directory());
+        assert_eq!(file_system.node(a).size, 10);
+        assert_eq!(file_system.node(root).size, 15);
+        assert_eq!(file_system.lookup("/a/b"), file_system.child(a, "b"));
+    }
+
+    #[test]
+    fn test_parse_transcript_real_input_has_no_diagnostics() {
+        // Act
+        let (_, diagnostics) = parse_transcript(super::INPUT);
+
+        // Assert
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_parse_transcript_paths_and_spaces() {
+        // Arrange
+        const INPUT: &str = "\
+$ ls
+dir a
+dir my docs
+$ cd /a
+$ ls
+dir b
+$ cd b/../../my docs
+$ ls
+100 hello world.txt
+$ cd /a/b
+$ ls
+50 c
+$ cd /
+$ ls
+dir my docs
+dir a
+";
+
+        // Act
+        let (file_system, diagnostics) = parse_transcript(INPUT);
+
+        // Assert
+        assert!(diagnostics.is_empty());
+        let file = file_system.lookup("/my docs/hello world.txt").unwrap();
+        assert_eq!(file_system.node(file).size, 100);
+        assert_eq!(file_system.node(file_system.root()).size, 150);
+    }
+
+    #[test]
+    fn test_parse_transcript_diagnostics() {
+        // Arrange
+        const INPUT: &str = "\
+$ cd /
+$ ls
+dir a
+10 b
+c
+$ cd x
+$ cd /b
+$ cd /
+$ cd ..
+$ ls
+20 b
+10 a
+$ rm b
+";
+        let expected = vec![
+            Diagnostic {
+                line: 5,
+                kind: DiagnosticKind::MalformedEntry(String::from("c")),
+            },
+            Diagnostic {
+                line: 6,
+                kind: DiagnosticKind::UnlistedDirectory {
+                    path: String::from("/x"),
+                },
+            },
+            Diagnostic {
+                line: 7,
+                kind: DiagnosticKind::NotADirectory {
+                    path: String::from("/b"),
+                },
+            },
+            Diagnostic {
+                line: 9,
+                kind: DiagnosticKind::AboveRoot,
+            },
+            Diagnostic {
+                line: 11,
+                kind: DiagnosticKind::ConflictingSize {
+                    path: String::from("/b"),
+                    previous: 10,
+                    size: 20,
+                },
+            },
+            Diagnostic {
+                line: 12,
+                kind: DiagnosticKind::ConflictingType {
+                    path: String::from("/a"),
+                },
+            },
+            Diagnostic {
+                line: 13,
+                kind: DiagnosticKind::UnknownCommand(String::from("rm b")),
+            },
+        ];
+
+        // Act
+        let (_, diagnostics) = parse_transcript(INPUT);
+
+        // Assert
+        assert_eq!(diagnostics, expected);
+        assert_eq!(
+            diagnostics[4].to_string(),
+            "line 11: /b listed with size 20 but was previously 10"
+        );
+    }
+
+    #[test]
+    fn test_parse_transcript_missing_from_relisting() {
+        // Arrange
+        const INPUT: &str = "\
+$ ls
+dir a
+10 b
+$ ls
+10 b
+50 c
+foo
+";
+
+        // Act
+        let (_, diagnostics) = parse_transcript(INPUT);
+
+        // Assert
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic {
+                    line: 4,
+                    kind: DiagnosticKind::MissingFromRelisting {
+                        path: String::from("/a"),
+                    },
+                },
+                Diagnostic {
+                    line: 7,
+                    kind: DiagnosticKind::MalformedEntry(String::from("foo")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_smallest_directory_to_free_configurable_sizes() {
+        // Arrange
+        let file_system = calculate_dir_structure(INPUT);
+
+        // Act
+        let already_free = smallest_directory_to_free(&file_system, 100_000_000, 30_000_000);
+        let tight = smallest_directory_to_free(&file_system, 48_381_165, 94_853);
+        let impossible = smallest_directory_to_free(&file_system, 48_381_165, 50_000_000);
+
+        // Assert
+        assert_eq!(already_free, Some(0));
+        assert_eq!(tight, Some(94_853));
+        assert_eq!(impossible, None);
+    }
+
+    #[test]
+    fn test_tree_and_du_reports() {
+        // Arrange
+        const INPUT: &str = "\
+$ cd /
+$ ls
+dir a
+10 b.txt
+$ cd a
+$ ls
+5 c
+dir d
+";
+        const EXPECTED_TREE: &str = "[15]  /
+├── [5]  a/
+│   ├── [5]  c
+│   └── [0]  d/
+└── [10]  b.txt
+";
+        const EXPECTED_DU: &str = "5\t/a/c\n0\t/a/d\n5\t/a\n10\t/b.txt\n15\t/\n";
+        let file_system = calculate_dir_structure(INPUT);
+
+        // Act
+        let tree = tree_report(&file_system);
+        let du = du_report(&file_system);
+
+        // Assert
+        assert_eq!(tree, EXPECTED_TREE);
+        assert_eq!(du, EXPECTED_DU);
+    }
+
+    #[test]
+    fn test_transcript_round_trip() {
+        // Arrange
+        let file_system = calculate_dir_structure(INPUT);
+
+        // Act
+        let transcript = to_transcript(&file_system);
+        let (round_tripped, diagnostics) = parse_transcript(&transcript);
+
+        // Assert
+        assert!(diagnostics.is_empty());
+        assert_eq!(du_report(&round_tripped), du_report(&file_system));
+        assert_eq!(part_1(&transcript), 95437);
+    }
+
+    #[test]
+    fn test_transcript_round_trip_keeps_surrounding_spaces() {
+        // Arrange
+        let mut file_system = FileSystem::default();
+        let root = file_system.root();
+        let directory = file_system.add_directory(root, "notes ").unwrap();
+        file_system.add_file(directory, " draft.txt ", 42);
+
+        // Act
+        let transcript = to_transcript(&file_system);
+        let (round_tripped, diagnostics) = parse_transcript(&transcript);
+
+        // Assert
+        assert!(diagnostics.is_empty());
+        assert_eq!(du_report(&round_tripped), du_report(&file_system));
+        let file = round_tripped.lookup("/notes / draft.txt ").unwrap();
+        assert_eq!(round_tripped.node(file).size, 42);
+    }
+
+    #[test]
+    fn test_from_directory() {
+        // Arrange
+        let root = std::env::temp_dir().join(format!("day07-test-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("a/e")).unwrap();
+        std::fs::write(root.join("b.txt"), [0; 100]).unwrap();
+        std::fs::write(root.join("a/f"), [0; 20]).unwrap();
+        std::fs::write(root.join("a/e/i"), [0; 5]).unwrap();
+
+        // Act
+        let file_system = FileSystem::from_directory(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+        let file_system = file_system.unwrap();
+
+        // Assert
+        assert_eq!(file_system.node(file_system.root()).size, 125);
+        assert_eq!(sum_of_directories_up_to(&file_system, 25), 30);
+        assert_eq!(
+            file_system.node(file_system.lookup("/a/e/i").unwrap()).size,
+            5
+        );
+    }
 }
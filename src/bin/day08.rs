@@ -1,33 +1,184 @@
 use forest::*;
+use render::*;
 
 const INPUT: &str = include_str!("../input/day08.txt");
 
+const USAGE: &str = "usage: day08 [<height|visibility|scenic> [ascii|ppm] [<input file>]]";
+
 #[cfg(not(tarpaulin))]
 fn main() {
-    println!("Part 1 => {}", part_1(INPUT));
-    println!("Part 2 => {}", part_2(INPUT));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() {
+        println!("Part 1 => {}", part_1(INPUT));
+        println!("Part 2 => {}", part_2(INPUT));
+        return;
+    }
+    if let Err(error) = run(&args) {
+        eprintln!("{error}\n{USAGE}");
+        std::process::exit(2);
+    }
+}
+
+// renders a forest, an error being a message to print along with the usage.
+#[cfg(not(tarpaulin))]
+fn run(args: &[String]) -> Result<(), String> {
+    use std::io::Write;
+
+    let mode = match args[0].as_str() {
+        "height" => RenderMode::Height,
+        "visibility" => RenderMode::Visibility,
+        "scenic" => RenderMode::ScenicScore,
+        other => return Err(format!("unknown render mode {other:?}")),
+    };
+    let input = match args.get(2) {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|error| format!("failed to read {path}: {error}"))?,
+        None => INPUT.to_string(),
+    };
+    // input files may use the separated format for trees taller than a byte.
+    let forest = input
+        .parse::<Forest<u32>>()
+        .map_err(|error| error.to_string())?;
+    let output = match args.get(1).map(|format| format.as_str()) {
+        None | Some("ascii") => render_ascii(&forest, mode).into_bytes(),
+        Some("ppm") => render_ppm(&forest, mode),
+        Some(other) => return Err(format!("unknown output format {other:?}")),
+    };
+    std::io::stdout()
+        .write_all(&output)
+        .map_err(|error| error.to_string())
 }
 
 mod forest {
-    use core::convert::Infallible;
+    use core::fmt::{self, Display, Formatter};
     use core::str::FromStr;
+    #[cfg(test)]
     use itertools::Either;
 
+    // problems with a forest's input, line numbers are 1-based.
+    #[derive(Debug, PartialEq)]
+    pub enum ParseForestError {
+        InvalidHeight {
+            line: usize,
+            text: String,
+        },
+        MixedFormats {
+            line: usize,
+        },
+        UnevenWidth {
+            line: usize,
+            width: usize,
+            expected: usize,
+        },
+    }
+
+    impl Display for ParseForestError {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match self {
+                Self::InvalidHeight { line, text } => {
+                    write!(f, "line {line}: {text:?} is not a valid height")
+                }
+                Self::MixedFormats { line } => write!(
+                    f,
+                    "line {line}: mixes digit and separated heights with earlier lines"
+                ),
+                Self::UnevenWidth {
+                    line,
+                    width,
+                    expected,
+                } => write!(
+                    f,
+                    "line {line}: has {width} trees but earlier lines have {expected}"
+                ),
+            }
+        }
+    }
+
+    // heights are a byte each by default, which covers the digit format. Forests written in the
+    // separated format with taller trees can be parsed with a wider height type.
     #[derive(Debug)]
-    pub struct Forest {
+    pub struct Forest<H = u8> {
         width: usize,
         height: usize,
-        data: Box<[u8]>,
+        data: Box<[H]>,
     }
 
-    impl Forest {
-        pub fn iter_visible(&self) -> impl Iterator<Item = ((usize, usize), u8)> + '_ {
+    impl<H: Copy + Ord> Forest<H> {
+        pub fn iter_visible(&self) -> impl Iterator<Item = ((usize, usize), H)> + '_ {
+            let visible = self.visibility();
+            self.iter()
+                .filter(move |(coord, _)| visible[self.coord_to_index(*coord)])
+        }
+
+        pub fn iter_scenic_scores(&self) -> impl Iterator<Item = u64> + '_ {
+            self.scenic_scores().into_iter()
+        }
+
+        // whether each tree can be seen from outside the forest, indexed like the data.
+        pub fn visibility(&self) -> Vec<bool> {
+            let mut visible = vec![false; self.data.len()];
+            self.sweep(|index, _, blocked| visible[index] |= !blocked);
+            visible
+        }
+
+        // the scenic score of each tree, indexed like the data. Scores are u64 as the product of
+        // four viewing distances overflows u32 in large forests.
+        pub fn scenic_scores(&self) -> Vec<u64> {
+            let mut scenic_scores = vec![1; self.data.len()];
+            self.sweep(|index, viewing_distance, _| {
+                scenic_scores[index] *= viewing_distance as u64
+            });
+            scenic_scores
+        }
+
+        // sweeps each row and column in both directions, calling visit with the index of each
+        // tree, its viewing distance in the direction swept from and whether a tree at least as
+        // tall blocks the view to that edge. During a sweep we keep a stack of the trees seen so
+        // far which aren't hidden behind a later, strictly taller tree (so the heights are
+        // non-increasing from bottom to top). For each tree, popping the shorter trees off the
+        // stack leaves the nearest tree blocking the view on top, and every tree is pushed and
+        // popped at most once.
+        fn sweep(&self, mut visit: impl FnMut(usize, usize, bool)) {
+            let mut stack = Vec::new();
+            let mut sweep_line = |indices: &mut dyn Iterator<Item = usize>| {
+                stack.clear();
+                indices.enumerate().for_each(|(position, index)| {
+                    let elem = self.data[index];
+                    while matches!(stack.last(), Some((_, top)) if *top < elem) {
+                        stack.pop();
+                    }
+                    match stack.last() {
+                        Some((blocking_position, _)) => {
+                            visit(index, position - blocking_position, true)
+                        }
+                        None => visit(index, position, false),
+                    }
+                    stack.push((position, elem));
+                });
+            };
+            (0..self.height).for_each(|y| {
+                let row = y * self.width..(y + 1) * self.width;
+                sweep_line(&mut row.clone());
+                sweep_line(&mut row.rev());
+            });
+            (0..self.width).for_each(|x| {
+                let column = (0..self.height).map(|y| y * self.width + x);
+                sweep_line(&mut column.clone());
+                sweep_line(&mut column.rev());
+            });
+        }
+
+        // the original implementations walking the four rays out from every tree, kept as
+        // a reference for the sweeps.
+        #[cfg(test)]
+        pub fn iter_visible_by_rays(&self) -> impl Iterator<Item = ((usize, usize), H)> + '_ {
             self.iter().filter(|(coord, elem_1)| {
                 self.is_tree_visible(*coord, |(_, elem_2)| elem_2 >= *elem_1)
             })
         }
 
-        pub fn iter_scenic_scores(&self) -> impl Iterator<Item = u32> + '_ {
+        #[cfg(test)]
+        pub fn iter_scenic_scores_by_rays(&self) -> impl Iterator<Item = u64> + '_ {
             self.iter().map(|(coord, elem_1)| {
                 self.scenic_score(coord, |encountered_block, (_, elem_2)| {
                     if *encountered_block {
@@ -42,7 +193,15 @@ mod forest {
             })
         }
 
-        fn iter(&self) -> impl Iterator<Item = ((usize, usize), u8)> + '_ {
+        pub fn dims(&self) -> (usize, usize) {
+            (self.width, self.height)
+        }
+
+        pub fn get(&self, coord: (usize, usize)) -> H {
+            self.data[self.coord_to_index(coord)]
+        }
+
+        pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), H)> + '_ {
             (0..self.data.len()).map(|index| {
                 let elem = self.data[index];
                 let coord = self.index_to_coord(index);
@@ -50,10 +209,11 @@ mod forest {
             })
         }
 
+        #[cfg(test)]
         fn iter_north(
             &self,
             from: (usize, usize),
-        ) -> impl Iterator<Item = ((usize, usize), u8)> + '_ {
+        ) -> impl Iterator<Item = ((usize, usize), H)> + '_ {
             let (x, y) = from;
             if y == 0 {
                 Either::Left(std::iter::empty())
@@ -67,10 +227,11 @@ mod forest {
             }
         }
 
+        #[cfg(test)]
         fn iter_south(
             &self,
             from: (usize, usize),
-        ) -> impl Iterator<Item = ((usize, usize), u8)> + '_ {
+        ) -> impl Iterator<Item = ((usize, usize), H)> + '_ {
             let (x, y) = from;
             if y == self.height - 1 {
                 Either::Left(std::iter::empty())
@@ -84,10 +245,11 @@ mod forest {
             }
         }
 
+        #[cfg(test)]
         fn iter_east(
             &self,
             from: (usize, usize),
-        ) -> impl Iterator<Item = ((usize, usize), u8)> + '_ {
+        ) -> impl Iterator<Item = ((usize, usize), H)> + '_ {
             let (x, y) = from;
             if x == self.width - 1 {
                 Either::Left(std::iter::empty())
@@ -101,10 +263,11 @@ mod forest {
             }
         }
 
+        #[cfg(test)]
         fn iter_west(
             &self,
             from: (usize, usize),
-        ) -> impl Iterator<Item = ((usize, usize), u8)> + '_ {
+        ) -> impl Iterator<Item = ((usize, usize), H)> + '_ {
             let (x, y) = from;
             if x == 0 {
                 Either::Left(std::iter::empty())
@@ -129,10 +292,11 @@ mod forest {
             y * self.width + x
         }
 
+        #[cfg(test)]
         fn is_tree_visible(
             &self,
             coord: (usize, usize),
-            mut func: impl FnMut(((usize, usize), u8)) -> bool,
+            mut func: impl FnMut(((usize, usize), H)) -> bool,
         ) -> bool {
             !self.iter_north(coord).any(&mut func)
                 || !self.iter_east(coord).any(&mut func)
@@ -140,47 +304,223 @@ mod forest {
                 || !self.iter_west(coord).any(&mut func)
         }
 
+        #[cfg(test)]
         fn scenic_score(
             &self,
             coord: (usize, usize),
-            mut func: impl FnMut(&mut bool, ((usize, usize), u8)) -> Option<()>,
-        ) -> u32 {
-            let score_north = self.iter_north(coord).scan(false, &mut func).count() as u32;
-            let score_west = self.iter_west(coord).scan(false, &mut func).count() as u32;
-            let score_south = self.iter_south(coord).scan(false, &mut func).count() as u32;
-            let score_east = self.iter_east(coord).scan(false, &mut func).count() as u32;
+            mut func: impl FnMut(&mut bool, ((usize, usize), H)) -> Option<()>,
+        ) -> u64 {
+            let score_north = self.iter_north(coord).scan(false, &mut func).count() as u64;
+            let score_west = self.iter_west(coord).scan(false, &mut func).count() as u64;
+            let score_south = self.iter_south(coord).scan(false, &mut func).count() as u64;
+            let score_east = self.iter_east(coord).scan(false, &mut func).count() as u64;
             score_north * score_west * score_south * score_east
         }
     }
 
-    impl FromStr for Forest {
-        type Err = Infallible;
+    impl<H: FromStr + From<u8>> FromStr for Forest<H> {
+        type Err = ParseForestError;
 
         fn from_str(input: &str) -> Result<Self, Self::Err> {
-            // parse the lines, get the width of the forest from a line
-            // along with the flattened data.
-            let (width, data) =
-                input
-                    .trim()
-                    .lines()
-                    .fold((0, Vec::<u8>::new()), |(_, mut data), line| {
-                        let line_length = line.trim().chars().fold(0, |line_length, c| {
-                            data.push(c.to_digit(10).unwrap() as u8);
-                            line_length + 1
+            // parse the lines into the flattened data. Lines are either a digit per tree or, for
+            // taller trees, heights separated by commas and/or whitespace. Every line has to use
+            // the same format as the first and be just as wide.
+            let mut data = Vec::new();
+            let mut is_separated_format = None;
+            let mut width = None;
+            let mut height = 0;
+            input
+                .trim()
+                .lines()
+                .enumerate()
+                .try_for_each(|(index, line)| {
+                    let line_number = index + 1;
+                    let line = line.trim();
+                    let is_separated = line.chars().any(|c| c == ',' || c.is_whitespace());
+                    if *is_separated_format.get_or_insert(is_separated) != is_separated {
+                        return Err(ParseForestError::MixedFormats { line: line_number });
+                    }
+                    let heights = if is_separated {
+                        line.split(|c: char| c == ',' || c.is_whitespace())
+                            .filter(|height| !height.is_empty())
+                            .map(|height| height.parse().map_err(|_| height.to_string()))
+                            .collect::<Result<Vec<H>, _>>()
+                    } else {
+                        line.chars()
+                            .map(|c| {
+                                c.to_digit(10)
+                                    .map(|digit| H::from(digit as u8))
+                                    .ok_or_else(|| c.to_string())
+                            })
+                            .collect()
+                    }
+                    .map_err(|text| ParseForestError::InvalidHeight {
+                        line: line_number,
+                        text,
+                    })?;
+                    let expected = *width.get_or_insert(heights.len());
+                    if heights.len() != expected {
+                        return Err(ParseForestError::UnevenWidth {
+                            line: line_number,
+                            width: heights.len(),
+                            expected,
                         });
-                        (line_length, data)
-                    });
-            let data = data.into_boxed_slice();
-
-            // height can be found from length of data divided by width.
-            let height = data.len() / width;
+                    }
+                    data.extend(heights);
+                    height += 1;
+                    Ok(())
+                })?;
 
             // create the forest.
             Ok(Self {
-                width,
+                width: width.unwrap_or(0),
                 height,
-                data,
+                data: data.into_boxed_slice(),
+            })
+        }
+    }
+}
+
+mod render {
+    use super::forest::Forest;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum RenderMode {
+        Height,
+        Visibility,
+        ScenicScore,
+    }
+
+    // how a single tree should be drawn, before picking characters or colours for it.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Cell {
+        // a value scaled into 0.0..=1.0 for the height and scenic score modes.
+        Level(f64),
+        Visible,
+        Hidden,
+        BestTree,
+        SightLine { horizontal: bool },
+    }
+
+    const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+    pub fn render_ascii<H: Copy + Ord + Into<f64>>(forest: &Forest<H>, mode: RenderMode) -> String {
+        let (width, _) = forest.dims();
+        // an empty forest has no rows to draw.
+        if width == 0 {
+            return String::new();
+        }
+        cells(forest, mode)
+            .chunks(width)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell {
+                        Cell::Level(level) => {
+                            char::from(ASCII_RAMP[(level * (ASCII_RAMP.len() - 1) as f64) as usize])
+                        }
+                        Cell::Visible => '#',
+                        Cell::Hidden => '.',
+                        Cell::BestTree => 'O',
+                        Cell::SightLine { horizontal: true } => '-',
+                        Cell::SightLine { horizontal: false } => '|',
+                    })
+                    .chain(std::iter::once('\n'))
+                    .collect::<String>()
             })
+            .collect()
+    }
+
+    // binary PPM (P6) image with a pixel per tree.
+    pub fn render_ppm<H: Copy + Ord + Into<f64>>(forest: &Forest<H>, mode: RenderMode) -> Vec<u8> {
+        let (width, height) = forest.dims();
+        let mut image = format!("P6\n{width} {height}\n255\n").into_bytes();
+        cells(forest, mode).into_iter().for_each(|cell| {
+            let colour = match cell {
+                Cell::Level(level) if mode == RenderMode::Height => {
+                    gradient([10, 40, 10], [120, 255, 120], level)
+                }
+                Cell::Level(level) => gradient([20, 20, 120], [255, 60, 20], level),
+                Cell::Visible => [40, 200, 40],
+                Cell::Hidden => [90, 30, 30],
+                Cell::BestTree => [255, 255, 255],
+                Cell::SightLine { .. } => [255, 220, 0],
+            };
+            image.extend(colour);
+        });
+        image
+    }
+
+    fn gradient(from: [u8; 3], to: [u8; 3], level: f64) -> [u8; 3] {
+        [0, 1, 2].map(|channel| {
+            let (from, to) = (from[channel] as f64, to[channel] as f64);
+            (from + (to - from) * level).round() as u8
+        })
+    }
+
+    fn cells<H: Copy + Ord + Into<f64>>(forest: &Forest<H>, mode: RenderMode) -> Vec<Cell> {
+        let (width, height) = forest.dims();
+        let scenic_scores = forest.scenic_scores();
+        let mut cells = match mode {
+            RenderMode::Height => {
+                let max_height = forest
+                    .iter()
+                    .map(|(_, elem)| elem)
+                    .max()
+                    .map_or(0.0, Into::into);
+                forest
+                    .iter()
+                    .map(|(_, elem)| Cell::Level(scale(elem.into(), max_height)))
+                    .collect::<Vec<_>>()
+            }
+            RenderMode::Visibility => forest
+                .visibility()
+                .into_iter()
+                .map(|visible| if visible { Cell::Visible } else { Cell::Hidden })
+                .collect(),
+            RenderMode::ScenicScore => {
+                let max_score = scenic_scores.iter().copied().max().unwrap_or(0);
+                scenic_scores
+                    .iter()
+                    .map(|score| Cell::Level(scale(*score as f64, max_score as f64)))
+                    .collect()
+            }
+        };
+
+        // highlight the tree with the best scenic score along with the trees it can see.
+        if let Some((best_index, _)) = scenic_scores
+            .iter()
+            .enumerate()
+            .max_by_key(|(index, score)| (**score, std::cmp::Reverse(*index)))
+        {
+            let best = (best_index % width, best_index / width);
+            let best_height = forest.get(best);
+            [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                .into_iter()
+                .for_each(|(dx, dy): (isize, isize)| {
+                    let mut coord = best;
+                    while let (Some(x), Some(y)) = (
+                        coord.0.checked_add_signed(dx).filter(|x| *x < width),
+                        coord.1.checked_add_signed(dy).filter(|y| *y < height),
+                    ) {
+                        coord = (x, y);
+                        cells[y * width + x] = Cell::SightLine {
+                            horizontal: dy == 0,
+                        };
+                        if forest.get(coord) >= best_height {
+                            break;
+                        }
+                    }
+                });
+            cells[best_index] = Cell::BestTree;
+        }
+        cells
+    }
+
+    fn scale(value: f64, max: f64) -> f64 {
+        if max == 0.0 {
+            0.0
+        } else {
+            value / max
         }
     }
 }
@@ -189,7 +529,7 @@ fn part_1(input: &str) -> u32 {
     input.parse::<Forest>().unwrap().iter_visible().count() as u32
 }
 
-fn part_2(input: &str) -> u32 {
+fn part_2(input: &str) -> u64 {
     input
         .parse::<Forest>()
         .unwrap()
@@ -225,7 +565,7 @@ mod tests {
     #[test]
     fn test_part_2() {
         // Arrange
-        const EXPECTED: u32 = 8;
+        const EXPECTED: u64 = 8;
 
         // Act
         let output = part_2(INPUT);
@@ -233,4 +573,187 @@ mod tests {
         // Assert
         assert_eq!(output, EXPECTED);
     }
+
+    #[test]
+    fn test_sweeps_match_rays() {
+        // Arrange
+        let mut seed = 12345_u32;
+        let input = (0..45)
+            .map(|_| {
+                (0..60)
+                    .map(|_| {
+                        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                        char::from(b'0' + ((seed >> 16) % 10) as u8)
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let forest = input.parse::<Forest>().unwrap();
+
+        // Act
+        let visible = forest.iter_visible().collect::<Vec<_>>();
+        let scenic_scores = forest.iter_scenic_scores().collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(visible, forest.iter_visible_by_rays().collect::<Vec<_>>());
+        assert_eq!(
+            scenic_scores,
+            forest.iter_scenic_scores_by_rays().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_scenic_score_beyond_u32() {
+        // Arrange
+        const SIZE: usize = 701;
+        const EXPECTED: u64 = 350 * 350 * 350 * 350;
+        let row = "0".repeat(SIZE);
+        let middle_row = format!("{}9{}", "0".repeat(SIZE / 2), "0".repeat(SIZE / 2));
+        let input = (0..SIZE)
+            .map(|y| {
+                if y == SIZE / 2 {
+                    middle_row.as_str()
+                } else {
+                    row.as_str()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // Act
+        let output = part_2(&input);
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_single_row_forest() {
+        // Arrange
+        let forest = "3103".parse::<Forest>().unwrap();
+
+        // Act
+        let visible = forest.iter_visible().count();
+        let scenic_scores = forest.iter_scenic_scores().collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(visible, 4);
+        assert_eq!(scenic_scores, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_separated_input_format() {
+        // Arrange
+        const SEPARATED: &str = "
+        3, 0, 3, 7, 3
+        2 5 5 1 2
+        6,5,3,3,2
+        3 3 5 4 9
+        3  5  3  9  0
+        ";
+        const TALL: &str = "
+        10 12 10
+        10 1000 10
+        10 11 10
+        ";
+
+        // Act
+        let output = (part_1(SEPARATED), part_2(SEPARATED));
+        let tall = TALL.parse::<Forest<u32>>().unwrap().iter_visible().count();
+        let too_tall = TALL.parse::<Forest>().map(|forest| forest.dims());
+
+        // Assert
+        assert_eq!(output, (21, 8));
+        assert_eq!(tall, 9);
+        assert_eq!(
+            too_tall,
+            Err(ParseForestError::InvalidHeight {
+                line: 2,
+                text: String::from("1000"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        // Arrange
+        let expected = [
+            Err(ParseForestError::InvalidHeight {
+                line: 2,
+                text: String::from("x"),
+            }),
+            Err(ParseForestError::InvalidHeight {
+                line: 1,
+                text: String::from("-3"),
+            }),
+            Err(ParseForestError::MixedFormats { line: 2 }),
+            Err(ParseForestError::UnevenWidth {
+                line: 3,
+                width: 2,
+                expected: 3,
+            }),
+        ];
+
+        // Act
+        let output = ["123\n4x6", "1 -3 2", "1 2 3\n456", "123\n456\n78"]
+            .map(|input| input.parse::<Forest>().map(|forest| forest.dims()));
+
+        // Assert
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        // Arrange
+        const EXPECTED_VISIBILITY: &str = "\
+#####
+##|.#
+##|##
+--O--
+##|##
+";
+        const EXPECTED_HEIGHT: &str = "- -#-\n:+|.:\n*+|-:\n--O--\n-+|@ \n";
+        let forest = INPUT.parse::<Forest>().unwrap();
+
+        // Act
+        let visibility = render_ascii(&forest, RenderMode::Visibility);
+        let height = render_ascii(&forest, RenderMode::Height);
+
+        // Assert
+        assert_eq!(visibility, EXPECTED_VISIBILITY);
+        assert_eq!(height, EXPECTED_HEIGHT);
+    }
+
+    #[test]
+    fn test_render_ppm() {
+        // Arrange
+        let forest = INPUT.parse::<Forest>().unwrap();
+        let header = b"P6\n5 5\n255\n";
+
+        // Act
+        let output = render_ppm(&forest, RenderMode::ScenicScore);
+
+        // Assert
+        assert_eq!(&output[..header.len()], header);
+        assert_eq!(output.len(), header.len() + 5 * 5 * 3);
+        let best_pixel = header.len() + (3 * 5 + 2) * 3;
+        assert_eq!(output[best_pixel..best_pixel + 3], [255, 255, 255]);
+        assert_eq!(output[header.len()..header.len() + 3], [20, 20, 120]);
+    }
+
+    #[test]
+    fn test_render_empty_forest() {
+        // Arrange
+        let forest = "".parse::<Forest>().unwrap();
+
+        // Act
+        let ascii = render_ascii(&forest, RenderMode::ScenicScore);
+        let ppm = render_ppm(&forest, RenderMode::Visibility);
+
+        // Assert
+        assert_eq!(forest.dims(), (0, 0));
+        assert_eq!(ascii, "");
+        assert_eq!(ppm, b"P6\n0 0\n255\n");
+    }
 }
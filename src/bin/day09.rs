@@ -1,66 +1,543 @@
-use std::collections::HashSet;
-
-type Coordinate = (i32, i32);
+use render::*;
+use rope::*;
 
 const INPUT: &str = include_str!("../input/day09.txt");
 
+const USAGE: &str = "usage: day09 [<ascii|ppm|gif> [steps|motions] [<knots>]]";
+
 #[cfg(not(tarpaulin))]
 fn main() {
-    println!("Part 1 => {}", part_1(INPUT));
-    println!("Part 2 => {}", part_2(INPUT));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() {
+        println!("Part 1 => {}", part_1(INPUT));
+        println!("Part 2 => {}", part_2(INPUT));
+        let rope = simulate::<2>(INPUT, 10);
+        println!(
+            "Positions visited by each knot => {:?}",
+            rope.trails()
+                .iter()
+                .map(|trail| trail.len())
+                .collect::<Vec<_>>()
+        );
+        println!(
+            "Head finishes at => {:?}, tail at => {:?}",
+            rope.head(),
+            rope.tail()
+        );
+        return;
+    }
+    if let Err(error) = run(&args) {
+        eprintln!("{error}\n{USAGE}");
+        std::process::exit(2);
+    }
 }
 
+// animates the rope, an error being a message to print along with the usage. The ppm frames are
+// written one after another, the gif is a single looping animation.
+#[cfg(not(tarpaulin))]
+fn run(args: &[String]) -> Result<(), String> {
+    use std::io::Write;
+
+    let mode = match args.get(1).map(|mode| mode.as_str()) {
+        None | Some("motions") => FrameMode::EndOfMotion,
+        Some("steps") => FrameMode::EveryStep,
+        Some(other) => return Err(format!("unknown frame mode {other:?}")),
+    };
+    let knots = match args.get(2) {
+        None => 10,
+        Some(knots) => knots
+            .parse()
+            .ok()
+            .filter(|knots| *knots > 0)
+            .ok_or_else(|| format!("invalid knot count {knots:?}"))?,
+    };
+    let motions = parse_motions(INPUT).map_err(|error| error.to_string())?;
+    let animation = Animation::new(&motions, knots, mode);
+    let mut stdout = std::io::stdout().lock();
+    match args[0].as_str() {
+        "ascii" => (0..animation.len()).try_for_each(|frame| {
+            writeln!(stdout, "== frame {frame} ==")?;
+            writeln!(stdout, "{}", animation.render_ascii(frame))
+        }),
+        "ppm" => (0..animation.len())
+            .try_for_each(|frame| stdout.write_all(&animation.render_ppm(frame, 4))),
+        "gif" => stdout.write_all(&animation.render_gif(4, 5)),
+        other => return Err(format!("unknown output format {other:?}")),
+    }
+    .map_err(|error| error.to_string())
+}
+
+// the first knot behind the head of a 10 knot rope follows exactly the same path as the tail
+// of a 2 knot rope, so both parts are answered by querying the trails of a single simulation.
 fn part_1(input: &str) -> usize {
-    calculate_with_points(input, 2)
+    simulate::<2>(input, 10).trail(1).len()
 }
 
 fn part_2(input: &str) -> usize {
-    calculate_with_points(input, 10)
+    simulate::<2>(input, 10).trail(9).len()
+}
+
+fn simulate<const D: usize>(input: &str, knots: usize) -> Rope<D> {
+    let motions = parse_motions::<D>(input).unwrap();
+    let mut rope = Rope::new(knots);
+    rope.run(&motions);
+    rope
 }
 
-fn calculate_with_points(input: &str, knots: usize) -> usize {
-    let (_, visited) = input.trim().lines().fold(
-        (
-            std::iter::repeat((0, 0)).take(knots).collect::<Vec<_>>(),
-            HashSet::new(),
-        ),
-        |(mut positions, mut visited), line| {
-            let mut splits = line.split_whitespace();
-            let direction = splits.next().unwrap();
-            let amount = splits.next().unwrap().parse::<u32>().unwrap();
-            (0..amount).for_each(|_| {
-                let (hx, hy) = positions[0];
-                let head = match direction {
-                    "R" => (hx + 1, hy),
-                    "L" => (hx - 1, hy),
-                    "U" => (hx, hy - 1),
-                    "D" => (hx, hy + 1),
-                    _ => unimplemented!(),
-                };
-                positions[0] = head;
-                (1..positions.len()).for_each(|index| {
-                    positions[index] = move_tail(positions[index - 1], positions[index]);
+mod render {
+    use super::rope::*;
+    use std::collections::{HashMap, HashSet};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum FrameMode {
+        EveryStep,
+        EndOfMotion,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Cell {
+        Empty,
+        Trail,
+        Start,
+        Knot(usize),
+    }
+
+    struct Frame {
+        knots: Vec<Coordinate<2>>,
+        // number of entries of the animation's trail which the tail had visited by this frame.
+        trail_length: usize,
+    }
+
+    // the frames of a rope simulation, starting with the initial state. The bounds cover every
+    // frame so that the picture doesn't shift around as the animation plays.
+    pub struct Animation {
+        frames: Vec<Frame>,
+        trail: Vec<Coordinate<2>>,
+        min: Coordinate<2>,
+        max: Coordinate<2>,
+    }
+
+    impl Animation {
+        pub fn new(motions: &[Motion<2>], knots: usize, mode: FrameMode) -> Self {
+            let mut rope = Rope::new(knots);
+            let mut trail = vec![rope.tail()];
+            let mut frames = vec![Frame {
+                knots: rope.knots().to_vec(),
+                trail_length: 1,
+            }];
+            let mut seen = HashSet::from([rope.tail()]);
+            rope.steps(motions).for_each(|step| {
+                let tail = step.knots[step.knots.len() - 1];
+                if seen.insert(tail) {
+                    trail.push(tail);
+                }
+                if mode == FrameMode::EveryStep || step.is_last_of_motion {
+                    frames.push(Frame {
+                        knots: step.knots,
+                        trail_length: trail.len(),
+                    });
+                }
+            });
+            let (min, max) = frames
+                .iter()
+                .flat_map(|frame| frame.knots.iter())
+                .chain(trail.iter())
+                .fold(([0, 0], [0, 0]), |(min, max), coord| {
+                    (
+                        [min[0].min(coord[0]), min[1].min(coord[1])],
+                        [max[0].max(coord[0]), max[1].max(coord[1])],
+                    )
                 });
-                visited.insert(positions[positions.len() - 1]);
+            Self {
+                frames,
+                trail,
+                min,
+                max,
+            }
+        }
+
+        pub fn len(&self) -> usize {
+            self.frames.len()
+        }
+
+        pub fn dims(&self) -> (usize, usize) {
+            (
+                (self.max[0] - self.min[0] + 1) as usize,
+                (self.max[1] - self.min[1] + 1) as usize,
+            )
+        }
+
+        // the head is drawn as H and the remaining knots numbered, other than the tail of a two
+        // knot rope which is drawn as T, to match the puzzle's diagrams.
+        pub fn render_ascii(&self, frame: usize) -> String {
+            let (width, _) = self.dims();
+            let knots = self.frames[frame].knots.len();
+            self.cells(frame)
+                .chunks(width)
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| match cell {
+                            Cell::Empty => '.',
+                            Cell::Trail => '#',
+                            Cell::Start => 's',
+                            Cell::Knot(0) => 'H',
+                            Cell::Knot(1) if knots == 2 => 'T',
+                            Cell::Knot(index) => char::from_digit(*index as u32, 36).unwrap_or('+'),
+                        })
+                        .chain(std::iter::once('\n'))
+                        .collect::<String>()
+                })
+                .collect()
+        }
+
+        // binary PPM (P6) image with each cell drawn as a scale x scale block of pixels.
+        pub fn render_ppm(&self, frame: usize, scale: usize) -> Vec<u8> {
+            let (width, height) = self.dims();
+            let knots = self.frames[frame].knots.len();
+            let mut image = format!("P6\n{} {}\n255\n", width * scale, height * scale).into_bytes();
+            image.extend(
+                self.scaled_pixels(frame, scale)
+                    .flat_map(|cell| colour(cell, knots)),
+            );
+            image
+        }
+
+        // animated GIF89a of every frame, looping forever with each frame shown for delay
+        // hundredths of a second and each cell drawn as a scale x scale block of pixels.
+        pub fn render_gif(&self, scale: usize, delay: u16) -> Vec<u8> {
+            let (width, height) = self.dims();
+            let dimension = |cells: usize| {
+                u16::try_from(cells * scale).expect("animation is too large for a gif")
+            };
+            let (image_width, image_height) = (dimension(width), dimension(height));
+
+            // every frame has the same knots, so the palette covers every cell of every frame.
+            let knots = self.frames[0].knots.len();
+            let mut palette = Vec::<[u8; 3]>::new();
+            [Cell::Empty, Cell::Trail, Cell::Start]
+                .into_iter()
+                .chain((0..knots).map(Cell::Knot))
+                .for_each(|cell| {
+                    let colour = colour(cell, knots);
+                    if !palette.contains(&colour) {
+                        palette.push(colour);
+                    }
+                });
+            let palette_bits = palette.len().next_power_of_two().max(2).trailing_zeros();
+            let lookup = palette
+                .iter()
+                .enumerate()
+                .map(|(index, colour)| (*colour, index as u8))
+                .collect::<HashMap<_, _>>();
+
+            let mut gif = b"GIF89a".to_vec();
+            gif.extend(image_width.to_le_bytes());
+            gif.extend(image_height.to_le_bytes());
+            gif.extend([0xF0 | (palette_bits - 1) as u8, 0, 0]);
+            gif.extend(palette.iter().flatten());
+            gif.resize(gif.len() + ((1 << palette_bits) - palette.len()) * 3, 0);
+            gif.extend(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00");
+            (0..self.frames.len()).for_each(|frame| {
+                gif.extend([0x21, 0xF9, 0x04, 0x00]);
+                gif.extend(delay.to_le_bytes());
+                gif.extend([0x00, 0x00, 0x2C, 0, 0, 0, 0]);
+                gif.extend(image_width.to_le_bytes());
+                gif.extend(image_height.to_le_bytes());
+                gif.push(0x00);
+                let min_code_size = palette_bits.max(2);
+                gif.push(min_code_size as u8);
+                let indices = self
+                    .scaled_pixels(frame, scale)
+                    .map(|cell| lookup[&colour(cell, knots)]);
+                lzw_literals(indices, min_code_size)
+                    .chunks(255)
+                    .for_each(|block| {
+                        gif.push(block.len() as u8);
+                        gif.extend(block);
+                    });
+                gif.push(0x00);
             });
-            (positions, visited)
-        },
-    );
-    visited.len()
+            gif.push(0x3B);
+            gif
+        }
+
+        // the cells of a frame in row order, repeated to fill a scale x scale block for each.
+        fn scaled_pixels(&self, frame: usize, scale: usize) -> impl Iterator<Item = Cell> {
+            let (width, height) = self.dims();
+            let cells = self.cells(frame);
+            (0..width * height * scale * scale).map(move |pixel| {
+                let (x, y) = (pixel % (width * scale), pixel / (width * scale));
+                cells[y / scale * width + x / scale]
+            })
+        }
+
+        fn cells(&self, frame: usize) -> Vec<Cell> {
+            let (width, height) = self.dims();
+            let frame = &self.frames[frame];
+            let index = |coord: Coordinate<2>| {
+                (coord[1] - self.min[1]) as usize * width + (coord[0] - self.min[0]) as usize
+            };
+            let mut cells = vec![Cell::Empty; width * height];
+            self.trail[..frame.trail_length]
+                .iter()
+                .for_each(|coord| cells[index(*coord)] = Cell::Trail);
+            cells[index([0, 0])] = Cell::Start;
+
+            // draw from the tail forwards so knots nearer the head are drawn on top.
+            frame
+                .knots
+                .iter()
+                .enumerate()
+                .rev()
+                .for_each(|(knot, coord)| cells[index(*coord)] = Cell::Knot(knot));
+            cells
+        }
+    }
+
+    fn colour(cell: Cell, knots: usize) -> [u8; 3] {
+        match cell {
+            Cell::Empty => [20, 20, 30],
+            Cell::Trail => [70, 90, 140],
+            Cell::Start => [40, 200, 40],
+            Cell::Knot(0) => [230, 40, 40],
+            Cell::Knot(index) => {
+                // fade from orange behind the head to yellow at the tail.
+                let level = index as f64 / (knots - 1) as f64;
+                [255, (140.0 + 100.0 * level) as u8, 40]
+            }
+        }
+    }
+
+    // GIF image data without any compression. Every index is written as a literal code one bit
+    // wider than the minimum code size, with a clear code before the decoder's code table would
+    // fill up enough for it to widen the codes.
+    fn lzw_literals(indices: impl Iterator<Item = u8>, min_code_size: u32) -> Vec<u8> {
+        let clear = 1 << min_code_size;
+        let end = clear + 1;
+        let width = min_code_size + 1;
+        let literals_per_clear = clear as usize - 2;
+        let mut bytes = Vec::new();
+        let (mut buffer, mut bits) = (0_u32, 0);
+        let mut write = |code: u32| {
+            buffer |= code << bits;
+            bits += width;
+            while bits >= 8 {
+                bytes.push(buffer as u8);
+                buffer >>= 8;
+                bits -= 8;
+            }
+        };
+        indices.enumerate().for_each(|(count, index)| {
+            if count % literals_per_clear == 0 {
+                write(clear);
+            }
+            write(index as u32);
+        });
+        write(end);
+        if bits > 0 {
+            bytes.push(buffer as u8);
+        }
+        bytes
+    }
 }
 
-fn move_tail(head: Coordinate, tail: Coordinate) -> Coordinate {
-    // get the distances on the x and y directions.
-    let ((hx, hy), (tx, ty)) = (head, tail);
-    let x = hx - tx;
-    let y = hy - ty;
+mod rope {
+    use core::fmt::{self, Display, Formatter};
+    use core::str::FromStr;
+    use std::collections::HashSet;
+
+    pub type Coordinate<const D: usize> = [i32; D];
+
+    // direction letters along with the axis they move along and which way. R/L and U/D are the
+    // x and y axes of the puzzle, F/B extend the rope into a third dimension.
+    const DIRECTIONS: [(char, usize, i32); 6] = [
+        ('R', 0, 1),
+        ('L', 0, -1),
+        ('U', 1, -1),
+        ('D', 1, 1),
+        ('F', 2, 1),
+        ('B', 2, -1),
+    ];
+
+    #[derive(Debug, PartialEq)]
+    pub enum ParseMotionError {
+        MissingDirection,
+        MissingAmount,
+        InvalidAmount(String),
+        UnknownDirection(char),
+        ConflictingDirections(String),
+    }
+
+    impl Display for ParseMotionError {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match self {
+                Self::MissingDirection => write!(f, "motion has no direction"),
+                Self::MissingAmount => write!(f, "motion has no amount"),
+                Self::InvalidAmount(amount) => write!(f, "{amount:?} is not a valid amount"),
+                Self::UnknownDirection(direction) => {
+                    write!(
+                        f,
+                        "{direction:?} is not a direction in this many dimensions"
+                    )
+                }
+                Self::ConflictingDirections(directions) => {
+                    write!(f, "{directions:?} moves along the same axis more than once")
+                }
+            }
+        }
+    }
 
-    // if the distances are both inside 2 then we don't need to move
-    // else we move by 1 space on the x and y axes in the appropriate directions.
-    if x.abs() < 2 && y.abs() < 2 {
-        tail
-    } else {
-        (tx + x.signum(), ty + y.signum())
+    // a head movement, the delta may move along several axes at once for diagonal moves such as UR.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Motion<const D: usize> {
+        pub delta: Coordinate<D>,
+        pub amount: u32,
+    }
+
+    impl<const D: usize> FromStr for Motion<D> {
+        type Err = ParseMotionError;
+
+        fn from_str(input: &str) -> Result<Self, Self::Err> {
+            let mut splits = input.split_whitespace();
+            let directions = splits.next().ok_or(ParseMotionError::MissingDirection)?;
+            let amount = splits.next().ok_or(ParseMotionError::MissingAmount)?;
+            let amount = amount
+                .parse()
+                .map_err(|_| ParseMotionError::InvalidAmount(amount.to_string()))?;
+            let mut delta = [0; D];
+            for direction in directions.chars() {
+                let &(_, axis, sign) = DIRECTIONS
+                    .iter()
+                    .find(|(letter, axis, _)| *letter == direction && *axis < D)
+                    .ok_or(ParseMotionError::UnknownDirection(direction))?;
+                if delta[axis] != 0 {
+                    return Err(ParseMotionError::ConflictingDirections(
+                        directions.to_string(),
+                    ));
+                }
+                delta[axis] = sign;
+            }
+            Ok(Self { delta, amount })
+        }
+    }
+
+    pub fn parse_motions<const D: usize>(input: &str) -> Result<Vec<Motion<D>>, ParseMotionError> {
+        input.trim().lines().map(|line| line.parse()).collect()
+    }
+
+    // a rope of knots starting on top of each other at the origin, every knot keeps a record of
+    // each position it has visited (including the origin).
+    #[derive(Debug)]
+    pub struct Rope<const D: usize> {
+        knots: Vec<Coordinate<D>>,
+        trails: Vec<HashSet<Coordinate<D>>>,
+    }
+
+    impl<const D: usize> Rope<D> {
+        pub fn new(knots: usize) -> Self {
+            assert!(knots > 0, "a rope needs at least one knot");
+            Self {
+                knots: vec![[0; D]; knots],
+                trails: vec![HashSet::from([[0; D]]); knots],
+            }
+        }
+
+        pub fn knots(&self) -> &[Coordinate<D>] {
+            &self.knots
+        }
+
+        pub fn head(&self) -> Coordinate<D> {
+            self.knots[0]
+        }
+
+        pub fn tail(&self) -> Coordinate<D> {
+            self.knots[self.knots.len() - 1]
+        }
+
+        pub fn trail(&self, knot: usize) -> &HashSet<Coordinate<D>> {
+            &self.trails[knot]
+        }
+
+        pub fn trails(&self) -> &[HashSet<Coordinate<D>>] {
+            &self.trails
+        }
+
+        // moves the head a single space by the given delta and lets the rest of the rope follow.
+        pub fn step(&mut self, delta: Coordinate<D>) {
+            (0..D).for_each(|axis| self.knots[0][axis] += delta[axis]);
+            self.trails[0].insert(self.knots[0]);
+            (1..self.knots.len()).for_each(|index| {
+                self.knots[index] = move_tail(self.knots[index - 1], self.knots[index]);
+                self.trails[index].insert(self.knots[index]);
+            });
+        }
+
+        pub fn steps<'a>(&'a mut self, motions: &'a [Motion<D>]) -> Steps<'a, D> {
+            Steps {
+                rope: self,
+                motions,
+                motion: 0,
+                taken: 0,
+            }
+        }
+
+        // applies all the motions, returning the number of single steps taken. Unlike steps this
+        // doesn't need a copy of the knots after every step.
+        pub fn run(&mut self, motions: &[Motion<D>]) -> usize {
+            motions.iter().fold(0, |taken, motion| {
+                (0..motion.amount).for_each(|_| self.step(motion.delta));
+                taken + motion.amount as usize
+            })
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Step<const D: usize> {
+        pub motion: usize,
+        pub is_last_of_motion: bool,
+        pub knots: Vec<Coordinate<D>>,
+    }
+
+    // iterator which applies the motions to the rope one step at a time, yielding the full
+    // position of the rope after each step.
+    pub struct Steps<'a, const D: usize> {
+        rope: &'a mut Rope<D>,
+        motions: &'a [Motion<D>],
+        motion: usize,
+        taken: u32,
+    }
+
+    impl<const D: usize> Iterator for Steps<'_, D> {
+        type Item = Step<D>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            // skip over any motions which have been completed (or which have no steps at all).
+            while self.taken == self.motions.get(self.motion)?.amount {
+                self.motion += 1;
+                self.taken = 0;
+            }
+            let Motion { delta, amount } = self.motions[self.motion];
+            self.rope.step(delta);
+            self.taken += 1;
+            Some(Step {
+                motion: self.motion,
+                is_last_of_motion: self.taken == amount,
+                knots: self.rope.knots.clone(),
+            })
+        }
+    }
+
+    fn move_tail<const D: usize>(head: Coordinate<D>, tail: Coordinate<D>) -> Coordinate<D> {
+        // get the distances along each axis.
+        let distances: [i32; D] = std::array::from_fn(|axis| head[axis] - tail[axis]);
+
+        // if the distances are all inside 2 then we don't need to move
+        // else we move by 1 space on every axis in the appropriate directions.
+        if distances.iter().all(|distance| distance.abs() < 2) {
+            tail
+        } else {
+            std::array::from_fn(|axis| tail[axis] + distances[axis].signum())
+        }
     }
 }
 
@@ -133,4 +610,271 @@ mod tests {
         // Assert
         assert_eq!(output, EXPECTED);
     }
+
+    #[test]
+    fn test_trails_are_recorded_for_every_knot() {
+        // Arrange
+        const INPUT: &str = "
+        R 4
+        U 4
+        L 3
+        D 1
+        R 4
+        D 1
+        L 5
+        R 2
+        ";
+        const EXPECTED: [usize; 10] = [21, 13, 7, 4, 3, 2, 1, 1, 1, 1];
+
+        // Act
+        let rope = simulate::<2>(INPUT, 10);
+        let output = rope
+            .trails()
+            .iter()
+            .map(|trail| trail.len())
+            .collect::<Vec<_>>();
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_steps_yield_full_rope_positions() {
+        // Arrange
+        let motions = parse_motions::<2>("R 2\nU 0\nUR 1").unwrap();
+        let expected = vec![
+            Step {
+                motion: 0,
+                is_last_of_motion: false,
+                knots: vec![[1, 0], [0, 0], [0, 0]],
+            },
+            Step {
+                motion: 0,
+                is_last_of_motion: true,
+                knots: vec![[2, 0], [1, 0], [0, 0]],
+            },
+            Step {
+                motion: 2,
+                is_last_of_motion: true,
+                knots: vec![[3, -1], [2, -1], [1, -1]],
+            },
+        ];
+        let mut rope = Rope::new(3);
+        let mut run_rope = Rope::new(3);
+
+        // Act
+        let output = rope.steps(&motions).collect::<Vec<_>>();
+        let taken = run_rope.run(&motions);
+
+        // Assert
+        assert_eq!(output, expected);
+        assert_eq!(taken, expected.len());
+        assert_eq!(run_rope.knots(), rope.knots());
+    }
+
+    #[test]
+    fn test_diagonal_motions() {
+        // Arrange
+        const INPUT: &str = "
+        UR 3
+        DL 1
+        DR 2
+        ";
+        const EXPECTED: ([i32; 2], [i32; 2], usize) = ([4, 0], [3, -1], 4);
+
+        // Act
+        let rope = simulate::<2>(INPUT, 2);
+        let output = (rope.head(), rope.tail(), rope.trail(1).len());
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_three_dimensional_rope() {
+        // Arrange
+        const INPUT: &str = "
+        F 3
+        U 2
+        RB 1
+        ";
+        const EXPECTED: ([i32; 3], [i32; 3]) = ([1, -2, 2], [0, -1, 3]);
+
+        // Act
+        let rope = simulate::<3>(INPUT, 2);
+        let output = (rope.head(), rope.tail());
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_parse_motion_errors() {
+        // Act
+        let output = (
+            "F 1".parse::<Motion<2>>(),
+            "UD 1".parse::<Motion<2>>(),
+            "R x".parse::<Motion<2>>(),
+            "R".parse::<Motion<3>>(),
+        );
+
+        // Assert
+        assert_eq!(output.0, Err(ParseMotionError::UnknownDirection('F')));
+        assert_eq!(
+            output.1,
+            Err(ParseMotionError::ConflictingDirections("UD".to_string()))
+        );
+        assert_eq!(
+            output.2,
+            Err(ParseMotionError::InvalidAmount("x".to_string()))
+        );
+        assert_eq!(output.3, Err(ParseMotionError::MissingAmount));
+    }
+
+    #[test]
+    fn test_render_ascii_end_of_motions() {
+        // Arrange
+        const INPUT: &str = "
+        R 4
+        U 4
+        L 3
+        D 1
+        R 4
+        D 1
+        L 5
+        R 2
+        ";
+        const EXPECTED_FIRST: &str = "\
+......
+......
+......
+......
+s##TH.
+";
+        const EXPECTED_LAST: &str = "\
+..##..
+...##.
+.TH##.
+....#.
+s###..
+";
+        let motions = parse_motions(INPUT).unwrap();
+
+        // Act
+        let animation = Animation::new(&motions, 2, FrameMode::EndOfMotion);
+
+        // Assert
+        assert_eq!(animation.len(), 9);
+        assert_eq!(animation.render_ascii(1), EXPECTED_FIRST);
+        assert_eq!(animation.render_ascii(8), EXPECTED_LAST);
+    }
+
+    #[test]
+    fn test_render_ascii_every_step_numbers_knots() {
+        // Arrange
+        const EXPECTED: &str = "\
+H.
+.1
+.2
+";
+        let motions = parse_motions("U 2\nL 1").unwrap();
+
+        // Act
+        let animation = Animation::new(&motions, 4, FrameMode::EveryStep);
+
+        // Assert
+        assert_eq!(animation.len(), 4);
+        assert_eq!(animation.render_ascii(3), EXPECTED);
+    }
+
+    #[test]
+    fn test_render_ppm() {
+        // Arrange
+        let motions = parse_motions("R 2").unwrap();
+        let header = b"P6\n6 2\n255\n";
+
+        // Act
+        let animation = Animation::new(&motions, 2, FrameMode::EveryStep);
+        let output = animation.render_ppm(2, 2);
+
+        // Assert
+        assert_eq!(&output[..header.len()], header);
+        assert_eq!(output.len(), header.len() + 6 * 2 * 3);
+        // the head is in the rightmost column, drawn 2 pixels wide on each of the 2 rows.
+        let head = header.len() + 4 * 3;
+        assert_eq!(output[head..head + 6], [230, 40, 40, 230, 40, 40]);
+        assert_eq!(output[head + 18..head + 24], [230, 40, 40, 230, 40, 40]);
+    }
+
+    #[test]
+    fn test_render_gif() {
+        // Arrange
+        let motions = parse_motions("R 2\nU 1").unwrap();
+        let animation = Animation::new(&motions, 3, FrameMode::EveryStep);
+
+        // Act
+        let output = animation.render_gif(2, 10);
+
+        // Assert
+        assert_eq!(&output[..10], b"GIF89a\x06\x00\x04\x00");
+        assert_eq!(output.last(), Some(&0x3B));
+        let frames = decode_gif_frames(&output);
+        assert_eq!(frames.len(), animation.len());
+        frames.iter().enumerate().for_each(|(frame, pixels)| {
+            let ppm = animation.render_ppm(frame, 2);
+            assert_eq!(pixels[..], ppm[ppm.len() - 6 * 4 * 3..]);
+        });
+    }
+
+    // reads back the pixels of each frame of a gif whose image data is all literal codes.
+    fn decode_gif_frames(gif: &[u8]) -> Vec<Vec<u8>> {
+        let palette_size = 2 << (gif[10] & 0x07);
+        let palette = &gif[13..13 + palette_size * 3];
+        let mut position = 13 + palette_size * 3;
+        let mut frames = Vec::new();
+        let read_blocks = |position: &mut usize| {
+            let mut data = Vec::<u8>::new();
+            while gif[*position] != 0 {
+                let length = gif[*position] as usize;
+                data.extend(&gif[*position + 1..*position + 1 + length]);
+                *position += length + 1;
+            }
+            *position += 1;
+            data
+        };
+        while gif[position] != 0x3B {
+            match gif[position] {
+                0x21 => {
+                    position += 2;
+                    read_blocks(&mut position);
+                }
+                0x2C => {
+                    position += 10;
+                    let min_code_size = gif[position] as u32;
+                    position += 1;
+                    let data = read_blocks(&mut position);
+                    let (clear, width) = (1 << min_code_size, min_code_size + 1);
+                    let mut pixels = Vec::new();
+                    let mut bit = 0;
+                    loop {
+                        let code = (0..width).fold(0, |code, offset| {
+                            let at = bit + offset as usize;
+                            code | ((data[at / 8] as u32 >> (at % 8)) & 1) << offset
+                        });
+                        bit += width as usize;
+                        if code == clear + 1 {
+                            break;
+                        }
+                        assert!(code <= clear, "only literal and clear codes are written");
+                        if code < clear {
+                            pixels.extend(&palette[code as usize * 3..code as usize * 3 + 3]);
+                        }
+                    }
+                    frames.push(pixels);
+                }
+                other => panic!("unexpected gif block {other:#x}"),
+            }
+        }
+        frames
+    }
 }
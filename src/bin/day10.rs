@@ -1,70 +1,669 @@
+use cpu::*;
+use ocr::*;
+use peripherals::*;
+
 const INPUT: &str = include_str!("../input/day10.txt");
+const CRT_WIDTH: usize = 40;
+const CRT_HEIGHT: usize = 6;
+
+const USAGE: &str = "usage: day10 [disassemble | trace [<cycle> | <register>=<value>]...]";
 
 #[cfg(not(tarpaulin))]
 fn main() {
-    println!("Part 1 => {}", part_1(INPUT));
-    println!("Part 2 => \n{}", part_2(INPUT));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() {
+        println!("Part 1 => {}", part_1(INPUT));
+        match part_2(INPUT) {
+            Ok(text) => println!("Part 2 => {text}"),
+            Err(error) => println!(
+                "Part 2 => {error}\n{}",
+                run_crt(INPUT, CRT_WIDTH, CRT_HEIGHT).render()
+            ),
+        }
+        return;
+    }
+    if let Err(error) = run(&args) {
+        eprintln!("{error}\n{USAGE}");
+        std::process::exit(2);
+    }
+}
+
+// inspects the program, an error being a message to print along with the usage.
+#[cfg(not(tarpaulin))]
+fn run(args: &[String]) -> Result<(), String> {
+    let program = InstructionSet::default()
+        .parse(INPUT)
+        .map_err(|error| error.to_string())?;
+    match args[0].as_str() {
+        "disassemble" => print!("{}", disassemble(&program)),
+        "trace" => {
+            let breakpoints = args[1..]
+                .iter()
+                .map(|breakpoint| match breakpoint.split_once('=') {
+                    Some((register, value)) => Ok(Breakpoint::Register {
+                        register: register.parse()?,
+                        value: value
+                            .parse()
+                            .map_err(|_| format!("{value:?} is not a register value"))?,
+                    }),
+                    None => breakpoint
+                        .parse()
+                        .map(Breakpoint::Cycle)
+                        .map_err(|_| format!("{breakpoint:?} is not a cycle")),
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let mut trace = Trace::default();
+            let mut cpu = Cpu::new(&program);
+            cpu.attach(&mut trace);
+            breakpoints
+                .into_iter()
+                .for_each(|breakpoint| cpu.add_breakpoint(breakpoint));
+            while let Halt::Breakpoint(breakpoint) = cpu.run() {
+                println!(
+                    "-- hit {breakpoint} after cycle {} with {} --",
+                    cpu.cycle(),
+                    cpu.registers()
+                );
+            }
+            drop(cpu);
+            trace.lines().iter().for_each(|line| println!("{line}"));
+        }
+        other => return Err(format!("unknown command {other:?}")),
+    }
+    Ok(())
 }
 
 fn part_1(input: &str) -> i32 {
-    iterate_cycles(input)
-        .map(|(index, register)| {
-            let during_cycle = index as i32 + 1; // index 19 is during cycle 20.
-            during_cycle * register
-        })
-        .skip(19) // skip first 19 because we want the first signal strength during 20th cycle
-        .step_by(40) // step by 40 each time
-        .sum()
+    // the first signal strength is sampled during the 20th cycle and every 40 cycles after.
+    let program = InstructionSet::default().parse(input).unwrap();
+    let mut sampler = SignalSampler::new(20, 40);
+    let mut cpu = Cpu::new(&program);
+    cpu.attach(&mut sampler);
+    cpu.run();
+    drop(cpu);
+    sampler.total()
 }
 
-fn part_2(input: &str) -> String {
-    iterate_cycles(input)
-        .take(240)
-        .flat_map(|(index, register)| {
-            let x = (index % 40) as i32;
-            let optional_newline = if index > 0 && x == 0 {
-                Some('\n')
-            } else {
-                None
-            };
-            let pixel_char = if x >= register - 1 && x <= register + 1 {
-                '#'
-            } else {
-                '.'
+fn part_2(input: &str) -> Result<String, OcrError> {
+    run_crt(input, CRT_WIDTH, CRT_HEIGHT).read()
+}
+
+fn run_crt(input: &str, width: usize, height: usize) -> Crt {
+    let program = InstructionSet::default().parse(input).unwrap();
+    let mut crt = Crt::new(width, height);
+    let mut cpu = Cpu::new(&program);
+    cpu.attach(&mut crt);
+    cpu.run();
+    drop(cpu);
+    crt
+}
+
+mod cpu {
+    use core::fmt::{self, Display, Formatter};
+    use core::str::FromStr;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Register {
+        X,
+        Y,
+        Z,
+        W,
+    }
+
+    impl Register {
+        const ALL: [Register; 4] = [Self::X, Self::Y, Self::Z, Self::W];
+
+        fn index(self) -> usize {
+            self as usize
+        }
+    }
+
+    impl Display for Register {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            let name = match self {
+                Self::X => 'x',
+                Self::Y => 'y',
+                Self::Z => 'z',
+                Self::W => 'w',
             };
-            optional_newline
+            write!(f, "{name}")
+        }
+    }
+
+    impl FromStr for Register {
+        type Err = String;
+
+        fn from_str(input: &str) -> Result<Self, Self::Err> {
+            Self::ALL
                 .into_iter()
-                .chain(std::iter::once(pixel_char))
-        })
-        .collect()
-}
+                .find(|register| register.to_string() == input)
+                .ok_or_else(|| format!("{input:?} is not a register"))
+        }
+    }
+
+    // every register starts with the value 1, like the X register of the puzzle.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Registers([i32; 4]);
+
+    impl Default for Registers {
+        fn default() -> Self {
+            Self([1; 4])
+        }
+    }
+
+    impl Registers {
+        pub fn get(&self, register: Register) -> i32 {
+            self.0[register.index()]
+        }
+
+        pub fn set(&mut self, register: Register, value: i32) {
+            self.0[register.index()] = value;
+        }
+    }
+
+    impl Display for Registers {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            Register::ALL
+                .iter()
+                .enumerate()
+                .try_for_each(|(index, register)| {
+                    let separator = if index == 0 { "" } else { " " };
+                    write!(f, "{separator}{register}={}", self.get(*register))
+                })
+        }
+    }
+
+    // an instruction takes a fixed number of cycles (at least 1) and its effect on the registers
+    // only becomes visible once the last of those cycles has finished.
+    pub trait Instruction: Display {
+        fn cycles(&self) -> usize;
+        fn execute(&self, registers: &mut Registers);
+    }
+
+    pub type Program = Vec<Box<dyn Instruction>>;
+
+    #[derive(Debug)]
+    pub struct Noop;
+
+    impl Instruction for Noop {
+        fn cycles(&self) -> usize {
+            1
+        }
+
+        fn execute(&self, _: &mut Registers) {}
+    }
+
+    impl Display for Noop {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            write!(f, "noop")
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct Add {
+        pub register: Register,
+        pub value: i32,
+    }
+
+    impl Instruction for Add {
+        fn cycles(&self) -> usize {
+            2
+        }
+
+        fn execute(&self, registers: &mut Registers) {
+            registers.set(self.register, registers.get(self.register) + self.value);
+        }
+    }
+
+    impl Display for Add {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            write!(f, "add{} {}", self.register, self.value)
+        }
+    }
+
+    pub type InstructionParser = fn(&str) -> Option<Box<dyn Instruction>>;
+
+    fn parse_noop(line: &str) -> Option<Box<dyn Instruction>> {
+        (line == "noop").then(|| Box::new(Noop) as Box<dyn Instruction>)
+    }
 
-fn iterate_cycles(input: &str) -> impl Iterator<Item = (usize, i32)> + '_ {
-    std::iter::once(1)
-        .chain(
+    fn parse_add(line: &str) -> Option<Box<dyn Instruction>> {
+        let (mnemonic, value) = line.strip_prefix("add")?.split_once(' ')?;
+        Some(Box::new(Add {
+            register: mnemonic.parse().ok()?,
+            value: value.trim().parse().ok()?,
+        }))
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct ParseError {
+        pub line: usize,
+        pub text: String,
+    }
+
+    impl Display for ParseError {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            write!(f, "line {}: unknown instruction {:?}", self.line, self.text)
+        }
+    }
+
+    // the instructions a program can be made from, each line is parsed by the first parser which
+    // recognises it. Further instructions can be supported by adding a parser for them.
+    pub struct InstructionSet {
+        parsers: Vec<InstructionParser>,
+    }
+
+    impl Default for InstructionSet {
+        fn default() -> Self {
+            Self {
+                parsers: Vec::new(),
+            }
+            .with(parse_noop)
+            .with(parse_add)
+        }
+    }
+
+    impl InstructionSet {
+        pub fn with(mut self, parser: InstructionParser) -> Self {
+            self.parsers.push(parser);
+            self
+        }
+
+        pub fn parse(&self, input: &str) -> Result<Program, ParseError> {
             input
                 .trim()
                 .lines()
-                .scan((1_i32, 1), |(previous, current), line| {
+                .enumerate()
+                .map(|(index, line)| {
                     let line = line.trim();
-                    let (delta, cycles) = if line == "noop" {
-                        (0, 1)
-                    } else if let Some(delta) = line.strip_prefix("addx ") {
-                        (delta.parse().unwrap(), 2)
-                    } else {
-                        unimplemented!()
-                    };
-                    *previous = *current;
-                    *current += delta;
-                    Some((*previous, *current, cycles))
+                    self.parsers
+                        .iter()
+                        .find_map(|parser| parser(line))
+                        .ok_or_else(|| ParseError {
+                            line: index + 1,
+                            text: line.to_string(),
+                        })
                 })
-                .flat_map(|(previous, current, cycles)| {
-                    std::iter::repeat(previous)
-                        .take(cycles - 1)
-                        .chain(std::iter::once(current))
-                }),
-        )
-        .enumerate()
+                .collect()
+        }
+    }
+
+    // address of each instruction alongside how many cycles it takes.
+    pub fn disassemble(program: &[Box<dyn Instruction>]) -> String {
+        program
+            .iter()
+            .enumerate()
+            .map(|(address, instruction)| {
+                format!(
+                    "{address:>4}: {:<12}; {} cycle(s)\n",
+                    instruction.to_string(),
+                    instruction.cycles()
+                )
+            })
+            .collect()
+    }
+
+    // what the peripherals attached to the cpu can see, cycles are numbered from 1.
+    pub struct Bus<'a> {
+        pub cycle: usize,
+        pub address: usize,
+        pub instruction: &'a dyn Instruction,
+        pub registers: &'a Registers,
+    }
+
+    pub trait Peripheral {
+        fn during_cycle(&mut self, _bus: &Bus) {}
+        fn after_cycle(&mut self, _bus: &Bus) {}
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Breakpoint {
+        // halts before the given cycle starts.
+        Cycle(usize),
+        // halts after a cycle which changed the register to the given value.
+        Register { register: Register, value: i32 },
+    }
+
+    impl Display for Breakpoint {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match self {
+                Self::Cycle(cycle) => write!(f, "breakpoint on cycle {cycle}"),
+                Self::Register { register, value } => {
+                    write!(f, "breakpoint on {register}={value}")
+                }
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Halt {
+        Finished,
+        Breakpoint(Breakpoint),
+    }
+
+    pub struct Cpu<'a> {
+        program: &'a [Box<dyn Instruction>],
+        registers: Registers,
+        address: usize,
+        cycle: usize,
+        // cycles left before the instruction at the current address finishes, 0 if it hasn't started.
+        remaining: usize,
+        peripherals: Vec<&'a mut dyn Peripheral>,
+        breakpoints: Vec<Breakpoint>,
+        // the cycle a cycle breakpoint last halted before, so that resuming doesn't halt again.
+        halted_before: Option<usize>,
+    }
+
+    impl<'a> Cpu<'a> {
+        pub fn new(program: &'a [Box<dyn Instruction>]) -> Self {
+            Self {
+                program,
+                registers: Registers::default(),
+                address: 0,
+                cycle: 0,
+                remaining: 0,
+                peripherals: vec![],
+                breakpoints: vec![],
+                halted_before: None,
+            }
+        }
+
+        pub fn attach(&mut self, peripheral: &'a mut dyn Peripheral) {
+            self.peripherals.push(peripheral);
+        }
+
+        pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+
+        pub fn registers(&self) -> &Registers {
+            &self.registers
+        }
+
+        // the number of cycles which have completed.
+        pub fn cycle(&self) -> usize {
+            self.cycle
+        }
+
+        // runs a single cycle, returning false if the program has already finished.
+        pub fn step(&mut self) -> bool {
+            let Some(instruction) = self.program.get(self.address) else {
+                return false;
+            };
+            if self.remaining == 0 {
+                self.remaining = instruction.cycles();
+                assert!(self.remaining > 0, "instructions take at least 1 cycle");
+            }
+            self.cycle += 1;
+            let bus_address = self.address;
+            let bus = Bus {
+                cycle: self.cycle,
+                address: bus_address,
+                instruction: instruction.as_ref(),
+                registers: &self.registers,
+            };
+            self.peripherals
+                .iter_mut()
+                .for_each(|peripheral| peripheral.during_cycle(&bus));
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                instruction.execute(&mut self.registers);
+                self.address += 1;
+            }
+            let bus = Bus {
+                cycle: self.cycle,
+                address: bus_address,
+                instruction: instruction.as_ref(),
+                registers: &self.registers,
+            };
+            self.peripherals
+                .iter_mut()
+                .for_each(|peripheral| peripheral.after_cycle(&bus));
+            true
+        }
+
+        // runs until the program finishes or a breakpoint is hit, can be called again to resume.
+        pub fn run(&mut self) -> Halt {
+            loop {
+                let next_cycle = self.cycle + 1;
+                if self.halted_before != Some(next_cycle) {
+                    if let Some(breakpoint) = self
+                        .breakpoints
+                        .iter()
+                        .find(|breakpoint| **breakpoint == Breakpoint::Cycle(next_cycle))
+                    {
+                        self.halted_before = Some(next_cycle);
+                        return Halt::Breakpoint(*breakpoint);
+                    }
+                }
+                let before = self.registers.clone();
+                if !self.step() {
+                    return Halt::Finished;
+                }
+                if let Some(breakpoint) = self.breakpoints.iter().find(|breakpoint| {
+                    matches!(breakpoint, Breakpoint::Register { register, value }
+                        if before.get(*register) != *value && self.registers.get(*register) == *value)
+                }) {
+                    return Halt::Breakpoint(*breakpoint);
+                }
+            }
+        }
+    }
+}
+
+mod ocr {
+    use core::fmt::{self, Display, Formatter};
+
+    pub const GLYPH_WIDTH: usize = 4;
+    pub const GLYPH_HEIGHT: usize = 6;
+
+    // glyphs are separated by a single dark column.
+    const GLYPH_STRIDE: usize = GLYPH_WIDTH + 1;
+
+    // the capital letters which are known to turn up in puzzle outputs, a row per line.
+    const FONT: [(char, [&str; GLYPH_HEIGHT]); 18] = [
+        ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+        ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+        ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+        ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+        ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+        ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+        ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+        ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+        ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+        ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+        ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+        ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+        ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+        ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+        ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('Y', ["#...", "#...", ".#.#", "..#.", "..#.", "..#."]),
+        ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+    ];
+
+    #[derive(Debug, PartialEq)]
+    pub enum OcrError {
+        UnsupportedHeight(usize),
+        UnknownGlyph {
+            line: usize,
+            position: usize,
+            // rows of the glyph separated by newlines.
+            glyph: String,
+        },
+    }
+
+    impl Display for OcrError {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match self {
+                Self::UnsupportedHeight(height) => write!(
+                    f,
+                    "a screen of height {height} can't be split into lines of {GLYPH_HEIGHT} pixels"
+                ),
+                Self::UnknownGlyph {
+                    line,
+                    position,
+                    glyph,
+                } => {
+                    write!(
+                        f,
+                        "unknown glyph at line {line}, position {position}:\n{glyph}"
+                    )
+                }
+            }
+        }
+    }
+
+    // reads the text from a screen which is a whole number of lines of glyphs tall, glyphs without
+    // any lit pixels are read as spaces. Line and position numbers are 1-based.
+    pub fn decode(pixels: &[bool], width: usize, height: usize) -> Result<String, OcrError> {
+        if height == 0 || !height.is_multiple_of(GLYPH_HEIGHT) {
+            return Err(OcrError::UnsupportedHeight(height));
+        }
+        (0..height / GLYPH_HEIGHT)
+            .map(|line| {
+                (0..(width + 1) / GLYPH_STRIDE)
+                    .map(|position| {
+                        let rows: [String; GLYPH_HEIGHT] = std::array::from_fn(|row| {
+                            let start =
+                                (line * GLYPH_HEIGHT + row) * width + position * GLYPH_STRIDE;
+                            pixels[start..start + GLYPH_WIDTH]
+                                .iter()
+                                .map(|lit| if *lit { '#' } else { '.' })
+                                .collect()
+                        });
+                        recognise(&rows).ok_or_else(|| OcrError::UnknownGlyph {
+                            line: line + 1,
+                            position: position + 1,
+                            glyph: rows.join("\n"),
+                        })
+                    })
+                    .collect::<Result<String, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|lines| lines.join("\n"))
+    }
+
+    fn recognise(glyph: &[String; GLYPH_HEIGHT]) -> Option<char> {
+        if glyph.iter().all(|row| !row.contains('#')) {
+            Some(' ')
+        } else {
+            FONT.iter()
+                .find(|(_, rows)| {
+                    rows.iter()
+                        .zip(glyph)
+                        .all(|(expected, row)| expected == row)
+                })
+                .map(|(letter, _)| *letter)
+        }
+    }
+}
+
+mod peripherals {
+    use super::cpu::*;
+    use super::ocr::*;
+
+    // adds up the signal strength (cycle number multiplied by X) during the sampled cycles.
+    pub struct SignalSampler {
+        first: usize,
+        interval: usize,
+        samples: Vec<i32>,
+    }
+
+    impl SignalSampler {
+        pub fn new(first: usize, interval: usize) -> Self {
+            Self {
+                first,
+                interval,
+                samples: vec![],
+            }
+        }
+
+        pub fn total(&self) -> i32 {
+            self.samples.iter().sum()
+        }
+    }
+
+    impl Peripheral for SignalSampler {
+        fn during_cycle(&mut self, bus: &Bus) {
+            if bus.cycle >= self.first && (bus.cycle - self.first).is_multiple_of(self.interval) {
+                self.samples
+                    .push(bus.cycle as i32 * bus.registers.get(Register::X));
+            }
+        }
+    }
+
+    // draws a pixel per cycle, lit when the 3 pixel wide sprite centred on X covers it.
+    pub struct Crt {
+        width: usize,
+        height: usize,
+        pixels: Vec<bool>,
+    }
+
+    impl Crt {
+        pub fn new(width: usize, height: usize) -> Self {
+            assert!(width > 0, "crt width must be non-zero");
+            Self {
+                width,
+                height,
+                pixels: Vec::with_capacity(width * height),
+            }
+        }
+
+        pub fn render(&self) -> String {
+            self.pixels
+                .chunks(self.width)
+                .map(|row| {
+                    row.iter()
+                        .map(|lit| if *lit { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+
+        // any pixels the program finished before drawing are treated as dark.
+        pub fn read(&self) -> Result<String, OcrError> {
+            let pixels = (0..self.width * self.height)
+                .map(|index| self.pixels.get(index).copied().unwrap_or(false))
+                .collect::<Vec<_>>();
+            decode(&pixels, self.width, self.height)
+        }
+    }
+
+    impl Peripheral for Crt {
+        fn during_cycle(&mut self, bus: &Bus) {
+            if self.pixels.len() < self.width * self.height {
+                let x = (self.pixels.len() % self.width) as i32;
+                let sprite = bus.registers.get(Register::X);
+                self.pixels.push((x - sprite).abs() <= 1);
+            }
+        }
+    }
+
+    // a line per cycle showing the instruction being executed and the registers during the cycle.
+    #[derive(Default)]
+    pub struct Trace {
+        lines: Vec<String>,
+    }
+
+    impl Trace {
+        pub fn lines(&self) -> &[String] {
+            &self.lines
+        }
+    }
+
+    impl Peripheral for Trace {
+        fn during_cycle(&mut self, bus: &Bus) {
+            self.lines.push(format!(
+                "{:>4} | {:>4}: {:<12} | {}",
+                bus.cycle,
+                bus.address,
+                bus.instruction.to_string(),
+                bus.registers
+            ));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -233,7 +832,7 @@ mod tests {
     }
 
     #[test]
-    fn test_part_2() {
+    fn test_render_crt() {
         // Arrange
         const EXPECTED: &str = "##..##..##..##..##..##..##..##..##..##..
 ###...###...###...###...###...###...###.
@@ -243,9 +842,216 @@ mod tests {
 #######.......#######.......#######.....";
 
         // Act
-        let output = part_2(INPUT);
+        let output = run_crt(INPUT, CRT_WIDTH, CRT_HEIGHT).render();
 
         // Assert
         assert_eq!(output, EXPECTED);
     }
+
+    #[test]
+    fn test_part_2_unknown_glyph() {
+        // Arrange
+        const EXPECTED: &str = "unknown glyph at line 1, position 1:
+##..
+###.
+####
+####
+####
+####";
+
+        // Act
+        let output = part_2(INPUT).unwrap_err().to_string();
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    // lays the glyphs of the text out over a screen of the given size, a line of text per 6 rows.
+    fn draw_text(text: &str, width: usize, height: usize) -> Vec<bool> {
+        const FONT: [(char, [&str; 6]); 3] = [
+            ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+            ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+            ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+        ];
+        let mut pixels = vec![false; width * height];
+        text.lines().enumerate().for_each(|(line, text)| {
+            text.chars().enumerate().for_each(|(position, letter)| {
+                if let Some((_, rows)) = FONT.iter().find(|(glyph, _)| *glyph == letter) {
+                    rows.iter().enumerate().for_each(|(row, pixel_row)| {
+                        pixel_row.chars().enumerate().for_each(|(column, pixel)| {
+                            let index = (line * 6 + row) * width + position * 5 + column;
+                            pixels[index] = pixel == '#';
+                        })
+                    })
+                }
+            })
+        });
+        pixels
+    }
+
+    #[test]
+    fn test_decode() {
+        // Arrange
+        const EXPECTED: &str = "EHZ\nZ E";
+        let pixels = draw_text(EXPECTED, 14, 12);
+
+        // Act
+        let output = decode(&pixels, 14, 12);
+
+        // Assert
+        assert_eq!(output, Ok(EXPECTED.to_string()));
+    }
+
+    #[test]
+    fn test_decode_unsupported_height() {
+        // Act
+        let output = decode(&[false; 40 * 5], 40, 5);
+
+        // Assert
+        assert_eq!(output, Err(OcrError::UnsupportedHeight(5)));
+    }
+
+    // 3 cycle instruction used to check the instruction set can be extended.
+    struct Mul {
+        register: Register,
+        value: i32,
+    }
+
+    impl Instruction for Mul {
+        fn cycles(&self) -> usize {
+            3
+        }
+
+        fn execute(&self, registers: &mut Registers) {
+            registers.set(self.register, registers.get(self.register) * self.value);
+        }
+    }
+
+    impl core::fmt::Display for Mul {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            write!(f, "mul{} {}", self.register, self.value)
+        }
+    }
+
+    fn parse_mul(line: &str) -> Option<Box<dyn Instruction>> {
+        let (register, value) = line.strip_prefix("mul")?.split_once(' ')?;
+        Some(Box::new(Mul {
+            register: register.parse().ok()?,
+            value: value.parse().ok()?,
+        }))
+    }
+
+    #[test]
+    fn test_extended_instruction_set() {
+        // Arrange
+        const PROGRAM: &str = "
+        addy 2
+        muly 4
+        mulx -3
+        noop
+        ";
+        const EXPECTED: (usize, i32, i32) = (9, -3, 12);
+        let program = InstructionSet::default()
+            .with(parse_mul)
+            .parse(PROGRAM)
+            .unwrap();
+        let mut cpu = Cpu::new(&program);
+
+        // Act
+        let halt = cpu.run();
+        let output = (
+            cpu.cycle(),
+            cpu.registers().get(Register::X),
+            cpu.registers().get(Register::Y),
+        );
+
+        // Assert
+        assert_eq!(halt, Halt::Finished);
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    #[should_panic(expected = "crt width must be non-zero")]
+    fn test_zero_width_crt_is_rejected() {
+        Crt::new(0, CRT_HEIGHT);
+    }
+
+    #[test]
+    fn test_unknown_instruction() {
+        // Act
+        let output = InstructionSet::default().parse("noop\nmulx 2").err();
+
+        // Assert
+        assert_eq!(
+            output,
+            Some(ParseError {
+                line: 2,
+                text: "mulx 2".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        // Arrange
+        let program = InstructionSet::default().parse(INPUT).unwrap();
+        let mut cpu = Cpu::new(&program);
+        cpu.add_breakpoint(Breakpoint::Cycle(20));
+        cpu.add_breakpoint(Breakpoint::Register {
+            register: Register::X,
+            value: 16,
+        });
+
+        // Act
+        let first = (cpu.run(), cpu.cycle(), cpu.registers().get(Register::X));
+        let second = (cpu.run(), cpu.cycle(), cpu.registers().get(Register::X));
+
+        // Assert
+        assert_eq!(
+            first,
+            (
+                Halt::Breakpoint(Breakpoint::Register {
+                    register: Register::X,
+                    value: 16
+                }),
+                2,
+                16
+            )
+        );
+        assert_eq!(second, (Halt::Breakpoint(Breakpoint::Cycle(20)), 19, 21));
+    }
+
+    #[test]
+    fn test_disassemble_and_trace() {
+        // Arrange
+        const PROGRAM: &str = "
+        noop
+        addx 3
+        addx -5
+        ";
+        const EXPECTED_DISASSEMBLY: &str = concat!(
+            "   0: noop        ; 1 cycle(s)\n",
+            "   1: addx 3      ; 2 cycle(s)\n",
+            "   2: addx -5     ; 2 cycle(s)\n",
+        );
+        const EXPECTED_TRACE: [&str; 5] = [
+            "   1 |    0: noop         | x=1 y=1 z=1 w=1",
+            "   2 |    1: addx 3       | x=1 y=1 z=1 w=1",
+            "   3 |    1: addx 3       | x=1 y=1 z=1 w=1",
+            "   4 |    2: addx -5      | x=4 y=1 z=1 w=1",
+            "   5 |    2: addx -5      | x=4 y=1 z=1 w=1",
+        ];
+        let program = InstructionSet::default().parse(PROGRAM).unwrap();
+        let mut trace = Trace::default();
+        let mut cpu = Cpu::new(&program);
+        cpu.attach(&mut trace);
+
+        // Act
+        cpu.run();
+        drop(cpu);
+
+        // Assert
+        assert_eq!(disassemble(&program), EXPECTED_DISASSEMBLY);
+        assert_eq!(trace.lines(), EXPECTED_TRACE);
+    }
 }
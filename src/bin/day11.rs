@@ -1,3 +1,5 @@
+use big_uint::BigUint;
+
 type Number = u64;
 
 const INPUT: &str = include_str!("../input/day11.txt");
@@ -5,190 +7,1141 @@ const INPUT: &str = include_str!("../input/day11.txt");
 #[cfg(not(tarpaulin))]
 fn main() {
     println!("Part 1 => {}", part_1(INPUT));
-    println!("Part 2 => {}", part_2(INPUT));
+    match part_2(INPUT) {
+        Ok(monkey_business) => println!("Part 2 => {monkey_business}"),
+        Err(error) => println!("Part 2 => {error}"),
+    }
+    println!("Part 1 (exact) => {}", part_1_exact(INPUT));
+    if let Err(error) = part_2_without_modulus(INPUT) {
+        println!("Part 2 (without the modulus) => {error}");
+    }
+    let report = INPUT
+        .parse::<VecMonkey>()
+        .unwrap()
+        .run_simulation(20, &[1, 20], true, |value: Number| value / 3)
+        .unwrap();
+    report.snapshots.iter().for_each(|snapshot| {
+        println!(
+            "Part 1 item locations after round {} => {:?}",
+            snapshot.round, snapshot.locations
+        )
+    });
+    println!(
+        "Part 1 path of item 0 => {:?}",
+        report.paths.as_ref().unwrap()[0]
+    );
+    match predict_part_2(INPUT, 1_000_000) {
+        Ok(prediction) => {
+            println!(
+                "Part 2 inspections after 1,000,000 rounds => {:?}",
+                prediction.inspections
+            );
+            if let Some(cycle) = prediction
+                .cycles
+                .iter()
+                .flatten()
+                .max_by_key(|cycle| cycle.length)
+            {
+                println!(
+                    "Part 2 longest item cycle => {} rounds, starting after round {}",
+                    cycle.length, cycle.start
+                );
+            }
+        }
+        Err(error) => println!("Part 2 inspections after 1,000,000 rounds => {error}"),
+    }
 }
 
 fn part_1(input: &str) -> Number {
     input
         .parse::<VecMonkey>()
         .unwrap()
-        .run_simulation(20, |value| value / 3)
+        .run_simulation(20, &[], false, |value: Number| value / 3)
+        .unwrap()
+        .monkey_business()
 }
 
-fn part_2(input: &str) -> Number {
+fn part_1_exact(input: &str) -> Number {
+    input
+        .parse::<VecMonkey>()
+        .unwrap()
+        .run_simulation(20, &[], false, |value: BigUint| value / BigUint::from(3))
+        .unwrap()
+        .monkey_business()
+}
+
+// worry levels are kept under the modulus when the monkeys allow it, otherwise they're tracked as
+// they are and the simulation fails if they overflow.
+fn part_2(input: &str) -> Result<Number, SimulationError> {
     let monkeys = input.parse::<VecMonkey>().unwrap();
-    let modulus = monkeys.iter_test_divisible_by_values().product::<Number>();
-    monkeys.run_simulation(10_000, |value| value % modulus)
+    let modulus = monkeys.modulus();
+    monkeys
+        .run_simulation(10_000, &[], false, |value: Number| {
+            modulus.map_or(value, |modulus| value % modulus)
+        })
+        .map(|report| report.monkey_business())
 }
 
-mod private {
-    use super::*;
-    use core::convert::Infallible;
-    use core::str::FromStr;
-    use itertools::*;
+// the same as part 2 but for any number of rounds, using the periodicity of each item's path.
+fn predict_part_2(input: &str, rounds: usize) -> Result<Prediction, SimulationError> {
+    let monkeys = input.parse::<VecMonkey>().unwrap();
+    let modulus = monkeys.modulus();
+    monkeys.predict_inspections(rounds, |value| {
+        modulus.map_or(value, |modulus| value % modulus)
+    })
+}
 
-    #[derive(Debug)]
-    enum Operation {
-        Squared,
-        Add(Number),
-        Multiply(Number),
+fn part_2_without_modulus(input: &str) -> Result<Number, SimulationError> {
+    input
+        .parse::<VecMonkey>()
+        .unwrap()
+        .run_simulation(10_000, &[], false, |value: Number| value)
+        .map(|report| report.monkey_business())
+}
+
+// arbitrary precision unsigned integers for tracking worry levels exactly, stored as base 2^32
+// digits with the least significant first and no trailing zero digits (so zero has no digits).
+mod big_uint {
+    use core::cmp::Ordering;
+    use core::fmt::{self, Display, Formatter};
+    use core::ops::{Add, Div, Mul, Rem, Sub};
+
+    #[derive(Clone, Debug, Default, Eq, PartialEq)]
+    pub struct BigUint {
+        digits: Vec<u32>,
+    }
+
+    impl BigUint {
+        fn normalise(mut self) -> Self {
+            while self.digits.last() == Some(&0) {
+                self.digits.pop();
+            }
+            self
+        }
+
+        // schoolbook long multiplication.
+        fn multiply(&self, rhs: &Self) -> Self {
+            let mut digits = vec![0_u32; self.digits.len() + rhs.digits.len()];
+            self.digits.iter().enumerate().for_each(|(i, lhs_digit)| {
+                let mut carry = 0_u64;
+                rhs.digits.iter().enumerate().for_each(|(j, rhs_digit)| {
+                    let product =
+                        *lhs_digit as u64 * *rhs_digit as u64 + digits[i + j] as u64 + carry;
+                    digits[i + j] = product as u32;
+                    carry = product >> 32;
+                });
+                digits[i + rhs.digits.len()] = carry as u32;
+            });
+            Self { digits }.normalise()
+        }
+
+        pub fn is_zero(&self) -> bool {
+            self.digits.is_empty()
+        }
+
+        pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+            if *self < *rhs {
+                return None;
+            }
+            let mut borrow = 0_i64;
+            let digits = self
+                .digits
+                .iter()
+                .enumerate()
+                .map(|(index, digit)| {
+                    let mut difference =
+                        *digit as i64 - rhs.digits.get(index).copied().unwrap_or(0) as i64 - borrow;
+                    borrow = (difference < 0) as i64;
+                    if difference < 0 {
+                        difference += 1 << 32;
+                    }
+                    difference as u32
+                })
+                .collect();
+            Some(Self { digits }.normalise())
+        }
+
+        // None on division by zero.
+        pub fn checked_div_rem(&self, rhs: &Self) -> Option<(Self, Self)> {
+            match rhs.digits[..] {
+                [] => None,
+                [divisor] => {
+                    // a single digit divisor can be handled a digit at a time.
+                    let mut remainder = 0_u64;
+                    let mut digits = self
+                        .digits
+                        .iter()
+                        .rev()
+                        .map(|digit| {
+                            let dividend = (remainder << 32) | *digit as u64;
+                            remainder = dividend % divisor as u64;
+                            (dividend / divisor as u64) as u32
+                        })
+                        .collect::<Vec<_>>();
+                    digits.reverse();
+                    Some((Self { digits }.normalise(), Self::from(remainder)))
+                }
+                _ => {
+                    // otherwise fall back to binary long division.
+                    let mut quotient = vec![0; self.digits.len()];
+                    let mut remainder = Self::default();
+                    (0..self.digits.len() * 32).rev().for_each(|bit| {
+                        remainder = remainder.clone() + remainder.clone();
+                        if self.digits[bit / 32] & (1 << (bit % 32)) != 0 {
+                            remainder = remainder.clone() + Self::from(1);
+                        }
+                        if remainder >= *rhs {
+                            remainder = remainder.checked_sub(rhs).unwrap();
+                            quotient[bit / 32] |= 1 << (bit % 32);
+                        }
+                    });
+                    Some((Self { digits: quotient }.normalise(), remainder))
+                }
+            }
+        }
+    }
+
+    impl From<u64> for BigUint {
+        fn from(value: u64) -> Self {
+            Self {
+                digits: vec![value as u32, (value >> 32) as u32],
+            }
+            .normalise()
+        }
+    }
+
+    impl Ord for BigUint {
+        fn cmp(&self, rhs: &Self) -> Ordering {
+            self.digits
+                .len()
+                .cmp(&rhs.digits.len())
+                .then_with(|| self.digits.iter().rev().cmp(rhs.digits.iter().rev()))
+        }
+    }
+
+    impl PartialOrd for BigUint {
+        fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+            Some(self.cmp(rhs))
+        }
+    }
+
+    impl Add for BigUint {
+        type Output = Self;
+
+        fn add(self, rhs: Self) -> Self {
+            let mut carry = 0_u64;
+            let mut digits = (0..self.digits.len().max(rhs.digits.len()))
+                .map(|index| {
+                    let sum = self.digits.get(index).copied().unwrap_or(0) as u64
+                        + rhs.digits.get(index).copied().unwrap_or(0) as u64
+                        + carry;
+                    carry = sum >> 32;
+                    sum as u32
+                })
+                .collect::<Vec<_>>();
+            digits.push(carry as u32);
+            Self { digits }.normalise()
+        }
+    }
+
+    impl Sub for BigUint {
+        type Output = Self;
+
+        fn sub(self, rhs: Self) -> Self {
+            self.checked_sub(&rhs)
+                .expect("attempt to subtract with overflow")
+        }
+    }
+
+    impl Mul for BigUint {
+        type Output = Self;
+
+        fn mul(self, rhs: Self) -> Self {
+            self.multiply(&rhs)
+        }
+    }
+
+    impl Div for BigUint {
+        type Output = Self;
+
+        fn div(self, rhs: Self) -> Self {
+            self.checked_div_rem(&rhs)
+                .expect("attempt to divide by zero")
+                .0
+        }
+    }
+
+    impl Rem for BigUint {
+        type Output = Self;
+
+        fn rem(self, rhs: Self) -> Self {
+            self.checked_div_rem(&rhs)
+                .expect("attempt to calculate the remainder with a divisor of zero")
+                .1
+        }
+    }
+
+    impl Display for BigUint {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            // split into base 10^9 chunks, least significant first.
+            const CHUNK: u64 = 1_000_000_000;
+            let mut chunks = vec![];
+            let mut value = self.clone();
+            while !value.is_zero() {
+                let (quotient, remainder) = value.checked_div_rem(&Self::from(CHUNK)).unwrap();
+                chunks.push(remainder.digits.first().copied().unwrap_or(0));
+                value = quotient;
+            }
+            match chunks.split_last() {
+                None => write!(f, "0"),
+                Some((most_significant, rest)) => {
+                    write!(f, "{most_significant}")?;
+                    rest.iter()
+                        .rev()
+                        .try_for_each(|chunk| write!(f, "{chunk:09}"))
+                }
+            }
+        }
+    }
+}
+
+// arithmetic expressions over a single variable for the operations, and conditions built from
+// them for the tests, e.g. "new % 7 == 0 and (new > 100 or divisible by 3)".
+mod expression {
+    use super::big_uint::BigUint;
+    use super::Number;
+    use core::fmt::{self, Display, Formatter};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Operator {
+        Add,
+        Subtract,
+        Multiply,
+        Divide,
+        Remainder,
+    }
+
+    // a type worry levels can be tracked with. Applying an operator gives None when the result
+    // can't be represented, such as on overflow, going below zero or division by zero.
+    pub trait Worry: Clone + Display + Ord {
+        fn from_number(number: Number) -> Self;
+        fn apply(&self, operator: Operator, rhs: &Self) -> Option<Self>;
+    }
+
+    impl Worry for Number {
+        fn from_number(number: Number) -> Self {
+            number
+        }
+
+        fn apply(&self, operator: Operator, rhs: &Self) -> Option<Self> {
+            match operator {
+                Operator::Add => self.checked_add(*rhs),
+                Operator::Subtract => self.checked_sub(*rhs),
+                Operator::Multiply => self.checked_mul(*rhs),
+                Operator::Divide => self.checked_div(*rhs),
+                Operator::Remainder => self.checked_rem(*rhs),
+            }
+        }
+    }
+
+    impl Worry for BigUint {
+        fn from_number(number: Number) -> Self {
+            Self::from(number)
+        }
+
+        fn apply(&self, operator: Operator, rhs: &Self) -> Option<Self> {
+            match operator {
+                Operator::Add => Some(self.clone() + rhs.clone()),
+                Operator::Subtract => self.checked_sub(rhs),
+                Operator::Multiply => Some(self.clone() * rhs.clone()),
+                Operator::Divide => self.checked_div_rem(rhs).map(|(quotient, _)| quotient),
+                Operator::Remainder => self.checked_div_rem(rhs).map(|(_, remainder)| remainder),
+            }
+        }
     }
 
-    impl Operation {
-        fn apply(&self, item: Number) -> Number {
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Expression {
+        Variable,
+        Literal(Number),
+        Binary(Operator, Box<Expression>, Box<Expression>),
+    }
+
+    impl Expression {
+        pub fn evaluate<W: Worry>(&self, variable: &W) -> Option<W> {
             match self {
-                Self::Squared => item * item,
-                Self::Add(amount) => item + amount,
-                Self::Multiply(amount) => item * amount,
+                Self::Variable => Some(variable.clone()),
+                Self::Literal(value) => Some(W::from_number(*value)),
+                Self::Binary(operator, lhs, rhs) => lhs
+                    .evaluate(variable)?
+                    .apply(*operator, &rhs.evaluate(variable)?),
             }
         }
     }
 
-    impl FromStr for Operation {
-        type Err = Infallible;
+    impl Expression {
+        // whether the expression only adds and multiplies, so that its value modulo any number
+        // only depends on the variable modulo that number.
+        pub fn is_modular(&self) -> bool {
+            match self {
+                Self::Variable | Self::Literal(_) => true,
+                Self::Binary(operator, lhs, rhs) => {
+                    matches!(operator, Operator::Add | Operator::Multiply)
+                        && lhs.is_modular()
+                        && rhs.is_modular()
+                }
+            }
+        }
+    }
 
-        fn from_str(input: &str) -> Result<Self, Self::Err> {
-            let input = input.trim();
-            if input == "old * old" {
-                Ok(Self::Squared)
-            } else if let Some(suffix) = input.strip_prefix("old + ") {
-                Ok(Self::Add(suffix.parse().unwrap()))
-            } else if let Some(suffix) = input.strip_prefix("old * ") {
-                Ok(Self::Multiply(suffix.parse().unwrap()))
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Comparison {
+        Less,
+        LessOrEqual,
+        Greater,
+        GreaterOrEqual,
+        Equal,
+        NotEqual,
+    }
+
+    impl Comparison {
+        fn apply<W: Worry>(self, lhs: W, rhs: W) -> bool {
+            match self {
+                Self::Less => lhs < rhs,
+                Self::LessOrEqual => lhs <= rhs,
+                Self::Greater => lhs > rhs,
+                Self::GreaterOrEqual => lhs >= rhs,
+                Self::Equal => lhs == rhs,
+                Self::NotEqual => lhs != rhs,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Condition {
+        DivisibleBy(Number),
+        Compare(Comparison, Expression, Expression),
+        Not(Box<Condition>),
+        And(Box<Condition>, Box<Condition>),
+        Or(Box<Condition>, Box<Condition>),
+    }
+
+    impl Condition {
+        pub fn evaluate<W: Worry>(&self, variable: &W) -> Option<bool> {
+            Some(match self {
+                Self::DivisibleBy(divisor) => {
+                    variable.apply(Operator::Remainder, &W::from_number(*divisor))?
+                        == W::from_number(0)
+                }
+                Self::Compare(comparison, lhs, rhs) => {
+                    comparison.apply(lhs.evaluate(variable)?, rhs.evaluate(variable)?)
+                }
+                Self::Not(condition) => !condition.evaluate(variable)?,
+                Self::And(lhs, rhs) => lhs.evaluate(variable)? && rhs.evaluate(variable)?,
+                Self::Or(lhs, rhs) => lhs.evaluate(variable)? || rhs.evaluate(variable)?,
+            })
+        }
+
+        // whether the condition is made only of divisibility checks, so that it only depends on
+        // the variable modulo the product of the divisors.
+        pub fn is_modular(&self) -> bool {
+            match self {
+                Self::DivisibleBy(_) => true,
+                Self::Compare(..) => false,
+                Self::Not(condition) => condition.is_modular(),
+                Self::And(lhs, rhs) | Self::Or(lhs, rhs) => lhs.is_modular() && rhs.is_modular(),
+            }
+        }
+
+        // every "divisible by" check within the condition.
+        pub fn divisors(&self) -> Vec<Number> {
+            match self {
+                Self::DivisibleBy(divisor) => vec![*divisor],
+                Self::Compare(..) => vec![],
+                Self::Not(condition) => condition.divisors(),
+                Self::And(lhs, rhs) | Self::Or(lhs, rhs) => {
+                    let mut divisors = lhs.divisors();
+                    divisors.extend(rhs.divisors());
+                    divisors
+                }
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum ParseError {
+        InvalidCharacter(char),
+        UnexpectedToken {
+            expected: &'static str,
+            found: String,
+        },
+        MissingToken {
+            expected: &'static str,
+        },
+    }
+
+    impl Display for ParseError {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match self {
+                Self::InvalidCharacter(character) => {
+                    write!(f, "unexpected character {character:?}")
+                }
+                Self::UnexpectedToken { expected, found } => {
+                    write!(f, "expected {expected} but found {found:?}")
+                }
+                Self::MissingToken { expected } => {
+                    write!(f, "expected {expected} but reached the end")
+                }
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Token {
+        Number(Number),
+        Word(String),
+        Symbol(&'static str),
+    }
+
+    impl Display for Token {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match self {
+                Self::Number(number) => write!(f, "{number}"),
+                Self::Word(word) => write!(f, "{word}"),
+                Self::Symbol(symbol) => write!(f, "{symbol}"),
+            }
+        }
+    }
+
+    // longer symbols come first so that "<=" isn't read as "<" followed by "=".
+    const SYMBOLS: [&str; 13] = [
+        "<=", ">=", "==", "!=", "<", ">", "+", "-", "*", "/", "%", "(", ")",
+    ];
+
+    fn tokenise(input: &str) -> Result<Vec<Token>, ParseError> {
+        let mut tokens = vec![];
+        let mut rest = input.trim_start();
+        while let Some(character) = rest.chars().next() {
+            let length = if character.is_ascii_digit() {
+                let length = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let number = &rest[..length];
+                tokens.push(Token::Number(number.parse().map_err(|_| {
+                    ParseError::UnexpectedToken {
+                        expected: "a number which fits",
+                        found: number.to_string(),
+                    }
+                })?));
+                length
+            } else if character.is_ascii_alphabetic() {
+                let length = rest
+                    .find(|c: char| !c.is_ascii_alphabetic())
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Word(rest[..length].to_string()));
+                length
             } else {
-                unimplemented!()
+                let symbol = SYMBOLS
+                    .into_iter()
+                    .find(|symbol| rest.starts_with(symbol))
+                    .ok_or(ParseError::InvalidCharacter(character))?;
+                tokens.push(Token::Symbol(symbol));
+                symbol.len()
+            };
+            rest = rest[length..].trim_start();
+        }
+        Ok(tokens)
+    }
+
+    // recursive descent parser, lowest precedence first: or, and, not, comparisons, + and -,
+    // then *, / and %.
+    struct Parser<'a> {
+        tokens: Vec<Token>,
+        position: usize,
+        variable: &'a str,
+    }
+
+    impl Parser<'_> {
+        fn peek(&self) -> Option<&Token> {
+            self.tokens.get(self.position)
+        }
+
+        fn next(&mut self, expected: &'static str) -> Result<Token, ParseError> {
+            let token = self
+                .tokens
+                .get(self.position)
+                .cloned()
+                .ok_or(ParseError::MissingToken { expected })?;
+            self.position += 1;
+            Ok(token)
+        }
+
+        fn expect(&mut self, expected: Token, description: &'static str) -> Result<(), ParseError> {
+            let token = self.next(description)?;
+            if token == expected {
+                Ok(())
+            } else {
+                Err(ParseError::UnexpectedToken {
+                    expected: description,
+                    found: token.to_string(),
+                })
+            }
+        }
+
+        fn accept_word(&mut self, word: &str) -> bool {
+            let accepted = matches!(self.peek(), Some(Token::Word(found)) if found == word);
+            if accepted {
+                self.position += 1;
+            }
+            accepted
+        }
+
+        fn accept_symbol(&mut self, symbols: &[&'static str]) -> Option<&'static str> {
+            match self.peek() {
+                Some(Token::Symbol(symbol)) if symbols.contains(symbol) => {
+                    let symbol = *symbol;
+                    self.position += 1;
+                    Some(symbol)
+                }
+                _ => None,
+            }
+        }
+
+        fn finish(&self) -> Result<(), ParseError> {
+            match self.peek() {
+                None => Ok(()),
+                Some(token) => Err(ParseError::UnexpectedToken {
+                    expected: "the end",
+                    found: token.to_string(),
+                }),
+            }
+        }
+
+        fn or(&mut self) -> Result<Condition, ParseError> {
+            let mut lhs = self.and()?;
+            while self.accept_word("or") {
+                lhs = Condition::Or(Box::new(lhs), Box::new(self.and()?));
+            }
+            Ok(lhs)
+        }
+
+        fn and(&mut self) -> Result<Condition, ParseError> {
+            let mut lhs = self.not()?;
+            while self.accept_word("and") {
+                lhs = Condition::And(Box::new(lhs), Box::new(self.not()?));
+            }
+            Ok(lhs)
+        }
+
+        fn not(&mut self) -> Result<Condition, ParseError> {
+            if self.accept_word("not") {
+                Ok(Condition::Not(Box::new(self.not()?)))
+            } else {
+                self.condition_atom()
+            }
+        }
+
+        fn condition_atom(&mut self) -> Result<Condition, ParseError> {
+            if self.accept_word("divisible") {
+                self.expect(Token::Word("by".to_string()), "\"by\"")?;
+                return match self.next("a divisor")? {
+                    Token::Number(divisor) => Ok(Condition::DivisibleBy(divisor)),
+                    token => Err(ParseError::UnexpectedToken {
+                        expected: "a divisor",
+                        found: token.to_string(),
+                    }),
+                };
+            }
+
+            // a parenthesis could open either a grouped condition or the first operand of a
+            // comparison, so try the condition first and fall back to the comparison.
+            let start = self.position;
+            if self.accept_symbol(&["("]).is_some() {
+                if let Ok(condition) = self.or() {
+                    if self.accept_symbol(&[")"]).is_some() {
+                        return Ok(condition);
+                    }
+                }
+                self.position = start;
+            }
+
+            let lhs = self.sum()?;
+            let comparison = match self.next("a comparison")? {
+                Token::Symbol("<") => Comparison::Less,
+                Token::Symbol("<=") => Comparison::LessOrEqual,
+                Token::Symbol(">") => Comparison::Greater,
+                Token::Symbol(">=") => Comparison::GreaterOrEqual,
+                Token::Symbol("==") => Comparison::Equal,
+                Token::Symbol("!=") => Comparison::NotEqual,
+                token => {
+                    return Err(ParseError::UnexpectedToken {
+                        expected: "a comparison",
+                        found: token.to_string(),
+                    })
+                }
+            };
+            Ok(Condition::Compare(comparison, lhs, self.sum()?))
+        }
+
+        fn sum(&mut self) -> Result<Expression, ParseError> {
+            let mut lhs = self.product()?;
+            while let Some(symbol) = self.accept_symbol(&["+", "-"]) {
+                let operator = if symbol == "+" {
+                    Operator::Add
+                } else {
+                    Operator::Subtract
+                };
+                lhs = Expression::Binary(operator, Box::new(lhs), Box::new(self.product()?));
+            }
+            Ok(lhs)
+        }
+
+        fn product(&mut self) -> Result<Expression, ParseError> {
+            let mut lhs = self.primary()?;
+            while let Some(symbol) = self.accept_symbol(&["*", "/", "%"]) {
+                let operator = match symbol {
+                    "*" => Operator::Multiply,
+                    "/" => Operator::Divide,
+                    _ => Operator::Remainder,
+                };
+                lhs = Expression::Binary(operator, Box::new(lhs), Box::new(self.primary()?));
+            }
+            Ok(lhs)
+        }
+
+        fn primary(&mut self) -> Result<Expression, ParseError> {
+            match self.next("a value")? {
+                Token::Number(value) => Ok(Expression::Literal(value)),
+                Token::Word(word) if word == self.variable => Ok(Expression::Variable),
+                Token::Symbol("(") => {
+                    let expression = self.sum()?;
+                    self.expect(Token::Symbol(")"), "\")\"")?;
+                    Ok(expression)
+                }
+                token => Err(ParseError::UnexpectedToken {
+                    expected: "a value",
+                    found: token.to_string(),
+                }),
             }
         }
     }
 
+    pub fn parse_expression(input: &str, variable: &str) -> Result<Expression, ParseError> {
+        let mut parser = Parser {
+            tokens: tokenise(input)?,
+            position: 0,
+            variable,
+        };
+        let expression = parser.sum()?;
+        parser.finish()?;
+        Ok(expression)
+    }
+
+    pub fn parse_condition(input: &str, variable: &str) -> Result<Condition, ParseError> {
+        let mut parser = Parser {
+            tokens: tokenise(input)?,
+            position: 0,
+            variable,
+        };
+        let condition = parser.or()?;
+        parser.finish()?;
+        Ok(condition)
+    }
+}
+
+mod private {
+    use super::expression::*;
+    use super::*;
+    use core::fmt::{self, Display, Formatter};
+    use core::str::FromStr;
+    use itertools::*;
+    use std::collections::HashMap;
+
     #[derive(Debug)]
     pub struct Monkey {
         items: Vec<Number>,
-        operation: Operation,
-        test_divisible_by: Number,
+        operation: Expression,
+        test: Condition,
         if_true_throw_to_monkey: usize,
         if_false_throw_to_monkey: usize,
     }
 
     impl Monkey {
-        fn catch_item(&mut self, item: Number) {
-            self.items.push(item);
+        // the new worry level for an item after inspecting it, and which monkey it's thrown to.
+        fn inspect<W: Worry>(
+            &self,
+            worry: &W,
+            relief: &mut impl FnMut(W) -> W,
+        ) -> Option<(W, usize)> {
+            let worry = relief(self.operation.evaluate(worry)?);
+            let throw_to_index = if self.test.evaluate(&worry)? {
+                self.if_true_throw_to_monkey
+            } else {
+                self.if_false_throw_to_monkey
+            };
+            Some((worry, throw_to_index))
         }
+    }
 
-        fn try_throw_first_item(
-            &mut self,
-            mut capping_func: impl FnMut(Number) -> Number,
-        ) -> Option<(Number, usize)> {
-            if self.items.is_empty() {
-                None
-            } else {
-                let item = self.items.remove(0);
-                let item = capping_func(self.operation.apply(item));
-                let throw_to_index = if item % self.test_divisible_by == 0 {
-                    self.if_true_throw_to_monkey
-                } else {
-                    self.if_false_throw_to_monkey
-                };
-                Some((item, throw_to_index))
+    // what's wrong with the description of a monkey, see ParseMonkeyError for which monkey.
+    #[derive(Debug, PartialEq)]
+    pub enum ParseMonkeyErrorKind {
+        MissingLine(&'static str),
+        InvalidItem(String),
+        InvalidOperation(ParseError),
+        InvalidTest(ParseError),
+        InvalidTarget(String),
+        UnknownTarget(usize),
+    }
+
+    // monkeys are numbered in the order they're listed in the input, from 0.
+    #[derive(Debug, PartialEq)]
+    pub struct ParseMonkeyError {
+        pub monkey: usize,
+        pub kind: ParseMonkeyErrorKind,
+    }
+
+    impl Display for ParseMonkeyError {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            write!(f, "monkey {}: ", self.monkey)?;
+            match &self.kind {
+                ParseMonkeyErrorKind::MissingLine(prefix) => {
+                    write!(f, "expected a line starting {prefix:?}")
+                }
+                ParseMonkeyErrorKind::InvalidItem(item) => {
+                    write!(f, "{item:?} is not a valid worry level")
+                }
+                ParseMonkeyErrorKind::InvalidOperation(error) => {
+                    write!(f, "invalid operation, {error}")
+                }
+                ParseMonkeyErrorKind::InvalidTest(error) => write!(f, "invalid test, {error}"),
+                ParseMonkeyErrorKind::InvalidTarget(target) => {
+                    write!(f, "{target:?} is not a monkey to throw to")
+                }
+                ParseMonkeyErrorKind::UnknownTarget(target) => {
+                    write!(f, "throws to monkey {target} which doesn't exist")
+                }
             }
         }
     }
 
-    impl<'a> FromIterator<&'a str> for Monkey {
-        fn from_iter<T>(iter: T) -> Self
-        where
-            T: IntoIterator<Item = &'a str>,
-        {
-            let mut iter = iter.into_iter().skip(1).map(|line| line.trim()); // skip first element because it's the monkey name/index which we can get from the index in the vector we'll place them in.
-            let items = iter
-                .next()
-                .unwrap()
-                .strip_prefix("Starting items: ")
-                .unwrap()
-                .split(", ")
-                .map(|worry| worry.parse().unwrap())
-                .collect();
-            let operation = iter
-                .next()
-                .unwrap()
-                .strip_prefix("Operation: new = ")
-                .unwrap()
-                .parse()
-                .unwrap();
-            let test_divisible_by = iter
-                .next()
-                .unwrap()
-                .strip_prefix("Test: divisible by ")
-                .unwrap()
-                .parse()
-                .unwrap();
-            let if_true_throw_to_monkey = iter
-                .next()
-                .unwrap()
-                .strip_prefix("If true: throw to monkey ")
-                .unwrap()
-                .parse()
-                .unwrap();
-            let if_false_throw_to_monkey = iter
-                .next()
-                .unwrap()
-                .strip_prefix("If false: throw to monkey ")
-                .unwrap()
-                .parse()
-                .unwrap();
-            Self {
+    impl Monkey {
+        // the lines describing a monkey, after the "Monkey <n>:" heading which is skipped as the
+        // index comes from the position in the vector we'll place them in.
+        fn parse<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Self, ParseMonkeyErrorKind> {
+            let mut lines = lines.skip(1).map(|line| line.trim());
+            let mut field = |prefix: &'static str| {
+                lines
+                    .next()
+                    .and_then(|line| line.strip_prefix(prefix))
+                    .ok_or(ParseMonkeyErrorKind::MissingLine(prefix))
+            };
+            let items = field("Starting items:")?
+                .split(',')
+                .map(|worry| worry.trim())
+                .filter(|worry| !worry.is_empty())
+                .map(|worry| {
+                    worry
+                        .parse()
+                        .map_err(|_| ParseMonkeyErrorKind::InvalidItem(worry.to_string()))
+                })
+                .collect::<Result<_, _>>()?;
+            let operation = parse_expression(field("Operation: new = ")?, "old")
+                .map_err(ParseMonkeyErrorKind::InvalidOperation)?;
+            let test = parse_condition(field("Test: ")?, "new")
+                .map_err(ParseMonkeyErrorKind::InvalidTest)?;
+            let mut target = |prefix| {
+                let target = field(prefix)?;
+                target
+                    .parse()
+                    .map_err(|_| ParseMonkeyErrorKind::InvalidTarget(target.to_string()))
+            };
+            let if_true_throw_to_monkey = target("If true: throw to monkey ")?;
+            let if_false_throw_to_monkey = target("If false: throw to monkey ")?;
+            Ok(Self {
                 items,
                 operation,
-                test_divisible_by,
+                test,
                 if_true_throw_to_monkey,
                 if_false_throw_to_monkey,
-            }
+            })
         }
     }
 
+    // an item a monkey couldn't inspect because its worry level went out of range. Rounds are
+    // 1-based and items are numbered in the order they're listed in the input, from 0.
+    #[derive(Debug, PartialEq)]
+    pub struct SimulationError {
+        pub round: usize,
+        pub monkey: usize,
+        pub item: usize,
+        pub worry: String,
+    }
+
+    impl Display for SimulationError {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            write!(
+                f,
+                "round {}: monkey {} couldn't inspect item {} with worry level {}",
+                self.round, self.monkey, self.item, self.worry
+            )
+        }
+    }
+
+    // which monkey is holding each item at the end of a round.
+    #[derive(Debug, PartialEq)]
+    pub struct Snapshot {
+        pub round: usize,
+        pub locations: Vec<usize>,
+    }
+
+    #[derive(Debug)]
+    pub struct Report {
+        pub inspections: Vec<Number>,
+        pub snapshots: Vec<Snapshot>,
+        // every monkey each item has been held by, starting with the monkey it started with.
+        // Only recorded when asked for, as they grow with every inspection.
+        pub paths: Option<Vec<Vec<usize>>>,
+    }
+
+    impl Report {
+        // the product of the inspection counts of the two most active monkeys.
+        pub fn monkey_business(&self) -> Number {
+            let mut inspections = self.inspections.clone();
+            inspections.sort_by(|item_1, item_2| item_2.cmp(item_1));
+            inspections[0] * inspections[1]
+        }
+    }
+
+    // an item returns to the same monkey with the same worry level every `length` rounds once
+    // `start` rounds have passed.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct ItemCycle {
+        pub start: usize,
+        pub length: usize,
+    }
+
+    #[derive(Debug)]
+    pub struct Prediction {
+        pub inspections: Vec<Number>,
+        // None for items which didn't repeat within the rounds asked for.
+        pub cycles: Vec<Option<ItemCycle>>,
+    }
+
     #[derive(Debug)]
     pub struct VecMonkey(Vec<Monkey>);
 
     impl VecMonkey {
-        pub fn run_simulation(
-            mut self,
+        // the starting monkey and worry level of every item, in item order.
+        fn iter_items(&self) -> impl Iterator<Item = (usize, Number)> + '_ {
+            self.0
+                .iter()
+                .enumerate()
+                .flat_map(|(index, monkey)| monkey.items.iter().map(move |worry| (index, *worry)))
+        }
+
+        // the worry type picks the mode, Number checks every operation for overflow and BigUint
+        // tracks worry levels exactly (only practical while the relief keeps them small).
+        pub fn run_simulation<W: Worry>(
+            &self,
             rounds: u16,
-            mut capping_func: impl FnMut(Number) -> Number,
-        ) -> Number {
-            let mut counts = std::iter::repeat(0).take(self.0.len()).collect::<Vec<_>>();
-            (0..rounds).for_each(|_| {
-                (0..self.0.len()).for_each(|index| {
-                    while let Some((item, throw_to_index)) =
-                        self.0[index].try_throw_first_item(&mut capping_func)
-                    {
-                        counts[index] += 1;
-                        self.0[throw_to_index].catch_item(item);
-                    }
-                });
+            snapshot_rounds: &[usize],
+            record_paths: bool,
+            mut relief: impl FnMut(W) -> W,
+        ) -> Result<Report, SimulationError> {
+            let mut held = vec![vec![]; self.0.len()];
+            self.iter_items()
+                .enumerate()
+                .for_each(|(item, (index, worry))| held[index].push((item, W::from_number(worry))));
+            let mut paths = record_paths.then(|| {
+                self.iter_items()
+                    .map(|(index, _)| vec![index])
+                    .collect::<Vec<_>>()
             });
-            counts.sort_by(|item_1, item_2| item_2.cmp(item_1));
-            counts[0] * counts[1]
+            let mut locations = self
+                .iter_items()
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>();
+            let mut inspections = std::iter::repeat_n(0, self.0.len()).collect::<Vec<_>>();
+            let mut snapshots = vec![];
+            for round in 1..=rounds as usize {
+                for index in 0..self.0.len() {
+                    for (item, worry) in std::mem::take(&mut held[index]) {
+                        let (worry, throw_to_index) = self.0[index]
+                            .inspect(&worry, &mut relief)
+                            .ok_or_else(|| SimulationError {
+                                round,
+                                monkey: index,
+                                item,
+                                worry: worry.to_string(),
+                            })?;
+                        inspections[index] += 1;
+                        locations[item] = throw_to_index;
+                        if let Some(paths) = paths.as_mut() {
+                            paths[item].push(throw_to_index);
+                        }
+                        held[throw_to_index].push((item, worry));
+                    }
+                }
+                if snapshot_rounds.contains(&round) {
+                    snapshots.push(Snapshot {
+                        round,
+                        locations: locations.clone(),
+                    });
+                }
+            }
+            Ok(Report {
+                inspections,
+                snapshots,
+                paths,
+            })
         }
 
-        pub fn iter_test_divisible_by_values(&self) -> impl Iterator<Item = Number> + '_ {
-            self.0.iter().map(|monkey| monkey.test_divisible_by)
+        // every item moves between the monkeys independently of the others, so while the relief
+        // keeps worry levels bounded (e.g. under a modulus) each item's trajectory eventually
+        // repeats. Only the rounds up to the first repeat need simulating for each item, the
+        // rest of the inspections follow from the length of the cycle.
+        pub fn predict_inspections(
+            &self,
+            rounds: usize,
+            mut relief: impl FnMut(Number) -> Number,
+        ) -> Result<Prediction, SimulationError> {
+            let mut inspections = vec![0; self.0.len()];
+            let mut cycles = vec![];
+            for (item, initial) in self.iter_items().enumerate() {
+                // counts covers every round run, which is all of them unless a repeat is found.
+                let mut counts = vec![0; self.0.len()];
+                let mut seen = HashMap::new();
+                let mut state = initial;
+                let mut cycle = None;
+                for round in 0..rounds {
+                    if let Some(start) = seen.insert(state, round) {
+                        cycle = Some(ItemCycle {
+                            start,
+                            length: round - start,
+                        });
+                        break;
+                    }
+                    state =
+                        self.run_item_round(item, round + 1, state, &mut relief, &mut counts)?;
+                }
+                if let Some(ItemCycle { start, length }) = cycle {
+                    // replay the rounds before the cycle and those into the final, partial cycle
+                    // to split the counts into the prefix and the inspections per cycle.
+                    let (full, partial) = ((rounds - start) / length, (rounds - start) % length);
+                    let mut prefix = vec![0; self.0.len()];
+                    let mut remainder = vec![0; self.0.len()];
+                    let mut state = initial;
+                    for round in 0..start + partial {
+                        let counts = if round < start {
+                            &mut prefix
+                        } else {
+                            &mut remainder
+                        };
+                        state = self.run_item_round(item, round + 1, state, &mut relief, counts)?;
+                    }
+                    counts = (0..self.0.len())
+                        .map(|index| {
+                            let per_cycle = counts[index] - prefix[index];
+                            prefix[index] + full as Number * per_cycle + remainder[index]
+                        })
+                        .collect();
+                }
+                inspections
+                    .iter_mut()
+                    .zip(counts)
+                    .for_each(|(total, count)| *total += count);
+                cycles.push(cycle);
+            }
+            Ok(Prediction {
+                inspections,
+                cycles,
+            })
+        }
+
+        // follows a single item through a round, it carries on being inspected in the same round
+        // for as long as it's thrown to monkeys which haven't had their turn yet.
+        fn run_item_round(
+            &self,
+            item: usize,
+            round: usize,
+            (mut index, mut worry): (usize, Number),
+            relief: &mut impl FnMut(Number) -> Number,
+            counts: &mut [Number],
+        ) -> Result<(usize, Number), SimulationError> {
+            loop {
+                let (new_worry, throw_to_index) = self.0[index]
+                    .inspect(&worry, relief)
+                    .ok_or_else(|| SimulationError {
+                        round,
+                        monkey: index,
+                        item,
+                        worry: worry.to_string(),
+                    })?;
+                counts[index] += 1;
+                let has_had_turn = throw_to_index <= index;
+                (index, worry) = (throw_to_index, new_worry);
+                if has_had_turn {
+                    return Ok((index, worry));
+                }
+            }
+        }
+
+        pub fn iter_test_divisors(&self) -> impl Iterator<Item = Number> + '_ {
+            self.0.iter().flat_map(|monkey| monkey.test.divisors())
+        }
+
+        // a modulus worry levels can be kept under without changing which monkey any item is
+        // thrown to, the product of the divisors in the tests. None unless every test only checks
+        // divisibility and every operation only adds and multiplies, as comparisons and the other
+        // operators depend on more than the remainder (or if the product is zero or overflows).
+        pub fn modulus(&self) -> Option<Number> {
+            if !self
+                .0
+                .iter()
+                .all(|monkey| monkey.operation.is_modular() && monkey.test.is_modular())
+            {
+                return None;
+            }
+            self.iter_test_divisors()
+                .try_fold(1, |product: Number, divisor| product.checked_mul(divisor))
+                .filter(|product| *product != 0)
         }
     }
 
     impl FromStr for VecMonkey {
-        type Err = Infallible;
+        type Err = ParseMonkeyError;
 
         fn from_str(input: &str) -> Result<Self, Self::Err> {
-            Ok(Self(
-                input
-                    .trim()
-                    .lines()
-                    .group_by(|line| line.trim().is_empty())
+            let monkeys = input
+                .trim()
+                .lines()
+                .group_by(|line| line.trim().is_empty())
+                .into_iter()
+                .filter(|(is_empty, _)| !is_empty)
+                .enumerate()
+                .map(|(monkey, (_, group))| {
+                    Monkey::parse(group).map_err(|kind| ParseMonkeyError { monkey, kind })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            monkeys
+                .iter()
+                .enumerate()
+                .try_for_each(|(monkey, parsed)| {
+                    [
+                        parsed.if_true_throw_to_monkey,
+                        parsed.if_false_throw_to_monkey,
+                    ]
                     .into_iter()
-                    .filter(|(is_empty, _)| !is_empty)
-                    .map(|(_, group)| group.collect())
-                    .collect(),
-            ))
+                    .find(|target| *target >= monkeys.len())
+                    .map_or(Ok(()), |target| {
+                        Err(ParseMonkeyError {
+                            monkey,
+                            kind: ParseMonkeyErrorKind::UnknownTarget(target),
+                        })
+                    })
+                })?;
+            Ok(Self(monkeys))
         }
     }
 }
@@ -196,6 +1149,8 @@ use private::*;
 
 #[cfg(test)]
 mod tests {
+    use super::big_uint::*;
+    use super::expression::*;
     use super::*;
 
     const INPUT: &str = "
@@ -243,10 +1198,346 @@ mod tests {
     #[test]
     fn test_part_2() {
         // Arrange
-        const EXPECTED: Number = 2713310158;
+        const EXPECTED: Result<Number, SimulationError> = Ok(2713310158);
+
+        // Act
+        let output = part_2(INPUT);
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_expression_precedence() {
+        // Arrange
+        const EXPECTED: Option<Number> = Some(35);
+        let expression = parse_expression("(old + 2) * 3 - old / 2 % 4", "old").unwrap();
+
+        // Act
+        let output = expression.evaluate(&10);
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_chained_conditions() {
+        // Arrange
+        const EXPECTED: [bool; 5] = [true, false, true, false, true];
+        let condition = parse_condition(
+            "divisible by 3 and not divisible by 5 or (new - 1) * 2 >= 200",
+            "new",
+        )
+        .unwrap();
+
+        // Act
+        let output = [9, 15, 150, 7, 101].map(|value: Number| condition.evaluate(&value).unwrap());
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+        assert_eq!(condition.divisors(), [3, 5]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        // Act
+        let output = (
+            parse_expression("old +", "old"),
+            parse_expression("old ^ 2", "old"),
+            parse_expression("old 2", "old"),
+            parse_condition("new + 1", "new"),
+        );
+
+        // Assert
+        assert_eq!(
+            output.0,
+            Err(ParseError::MissingToken {
+                expected: "a value"
+            })
+        );
+        assert_eq!(output.1, Err(ParseError::InvalidCharacter('^')));
+        assert_eq!(
+            output.2,
+            Err(ParseError::UnexpectedToken {
+                expected: "the end",
+                found: "2".to_string()
+            })
+        );
+        assert_eq!(
+            output.3,
+            Err(ParseError::MissingToken {
+                expected: "a comparison"
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_monkey_errors() {
+        // Arrange
+        const MONKEY: &str = "
+        Monkey 0:
+            Starting items: 1, 2
+            Operation: new = old * 2
+            Test: divisible by 2
+                If true: throw to monkey 1
+                If false: throw to monkey 0
+        ";
+        let expected = [
+            ParseMonkeyErrorKind::InvalidItem(String::from("x")),
+            ParseMonkeyErrorKind::InvalidOperation(ParseError::InvalidCharacter('^')),
+            ParseMonkeyErrorKind::MissingLine("Test: "),
+            ParseMonkeyErrorKind::InvalidTarget(String::from("one")),
+            ParseMonkeyErrorKind::UnknownTarget(2),
+        ]
+        .map(|kind| Err(ParseMonkeyError { monkey: 1, kind }));
+
+        // Act
+        let output = [
+            ("1, 2", "1, x"),
+            ("old * 2", "old ^ 2"),
+            ("Test: divisible by 2", "Check: divisible by 2"),
+            ("monkey 1", "monkey one"),
+            ("monkey 1", "monkey 2"),
+        ]
+        .map(|(from, to)| {
+            format!("{MONKEY}\n{}", MONKEY.replace("0:", "1:").replace(from, to))
+                .parse::<VecMonkey>()
+                .map(|_| ())
+        });
+
+        // Assert
+        assert_eq!(output, expected);
+        assert_eq!(
+            expected[4].as_ref().unwrap_err().to_string(),
+            "monkey 1: throws to monkey 2 which doesn't exist"
+        );
+    }
+
+    #[test]
+    fn test_comparison_threshold_monkeys() {
+        // Arrange
+        const INPUT: &str = "
+        Monkey 0:
+            Starting items: 10, 40, 95
+            Operation: new = (old + 1) * 2 - old / 3
+            Test: new > 30 or new % 7 == 0
+                If true: throw to monkey 1
+                If false: throw to monkey 2
+
+        Monkey 1:
+            Starting items: 3
+            Operation: new = old * 3 - old / 2 + 1
+            Test: (new < 10) and not divisible by 2
+                If true: throw to monkey 2
+                If false: throw to monkey 0
+
+        Monkey 2:
+            Starting items: 17, 8
+            Operation: new = old + 5
+            Test: divisible by 4
+                If true: throw to monkey 0
+                If false: throw to monkey 1
+        ";
+        const EXPECTED: Number = 3904;
+
+        // Act
+        let output = INPUT
+            .parse::<VecMonkey>()
+            .unwrap()
+            .run_simulation(20, &[], false, |value: Number| value / 3)
+            .unwrap()
+            .monkey_business();
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_part_2_without_a_safe_modulus() {
+        // Arrange
+        const INPUT: &str = "
+        Monkey 0:
+            Starting items: 10, 40, 95
+            Operation: new = old / 2 + 7
+            Test: new > 30
+                If true: throw to monkey 1
+                If false: throw to monkey 2
+
+        Monkey 1:
+            Starting items: 3
+            Operation: new = old + 4
+            Test: divisible by 3
+                If true: throw to monkey 0
+                If false: throw to monkey 2
+
+        Monkey 2:
+            Starting items: 17, 8
+            Operation: new = old * 3 % 100
+            Test: divisible by 4
+                If true: throw to monkey 0
+                If false: throw to monkey 1
+        ";
+        const EXPECTED: Result<Number, SimulationError> = Ok(1805315000);
+        let monkeys = INPUT.parse::<VecMonkey>().unwrap();
+        let modulus = monkeys.iter_test_divisors().product::<Number>();
 
         // Act
         let output = part_2(INPUT);
+        let with_modulus = monkeys
+            .run_simulation(10_000, &[], false, |value: Number| value % modulus)
+            .map(|report| report.monkey_business());
+
+        // Assert
+        assert_eq!(monkeys.modulus(), None);
+        assert_eq!(output, EXPECTED);
+        assert_eq!(output, part_2_without_modulus(INPUT));
+        assert_ne!(output, with_modulus);
+    }
+
+    fn from_u128(value: u128) -> BigUint {
+        BigUint::from((value >> 64) as u64) * BigUint::from(1 << 32) * BigUint::from(1 << 32)
+            + BigUint::from(value as u64)
+    }
+
+    #[test]
+    fn test_big_uint_matches_u128() {
+        // Arrange
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            // vary the sizes so both the single and multiple digit divisions get used.
+            state >> (state % 64)
+        };
+
+        // Act / Assert
+        (0..500).for_each(|_| {
+            let (lhs, rhs) = (next() as u128, next().max(1) as u128);
+            let (big_lhs, big_rhs) = (from_u128(lhs), from_u128(rhs));
+            assert_eq!(big_lhs.clone() + big_rhs.clone(), from_u128(lhs + rhs));
+            assert_eq!(big_lhs.clone() * big_rhs.clone(), from_u128(lhs * rhs));
+            assert_eq!(big_lhs.clone() / big_rhs.clone(), from_u128(lhs / rhs));
+            assert_eq!(big_lhs.clone() % big_rhs.clone(), from_u128(lhs % rhs));
+            assert_eq!(
+                big_lhs.checked_sub(&big_rhs),
+                lhs.checked_sub(rhs).map(from_u128)
+            );
+            assert_eq!(big_lhs.cmp(&big_rhs), lhs.cmp(&rhs));
+        });
+    }
+
+    #[test]
+    fn test_big_uint_division_beyond_u128() {
+        // Arrange
+        let quotient = (1..=30)
+            .map(BigUint::from)
+            .fold(BigUint::from(1), |a, b| a * b);
+        let divisor = from_u128(0x1234_5678_9abc_def0_1122_3344_5566_7788);
+        let remainder = from_u128(0x1234_5678_9abc_def0_1122_3344_5566_7787);
+        let dividend = quotient.clone() * divisor.clone() + remainder.clone();
+
+        // Act
+        let output = dividend.checked_div_rem(&divisor);
+
+        // Assert
+        assert_eq!(output, Some((quotient.clone(), remainder)));
+        assert_eq!(quotient.to_string(), "265252859812191058636308480000000");
+        assert_eq!(dividend.checked_div_rem(&BigUint::from(0)), None);
+    }
+
+    #[test]
+    fn test_exact_mode() {
+        // Arrange
+        const EXPECTED: Number = 10605;
+
+        // Act
+        let output = part_1_exact(INPUT);
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_checked_mode_reports_overflow() {
+        // Arrange
+        let expected = SimulationError {
+            round: 13,
+            monkey: 0,
+            item: 0,
+            worry: "13988703546165100909".to_string(),
+        };
+
+        // Act
+        let output = part_2_without_modulus(INPUT);
+
+        // Assert
+        assert_eq!(output, Err(expected));
+    }
+
+    #[test]
+    fn test_report() {
+        // Arrange
+        const EXPECTED_INSPECTIONS: [Number; 4] = [101, 95, 7, 105];
+        let monkeys = INPUT.parse::<VecMonkey>().unwrap();
+
+        // Act
+        let output = monkeys
+            .run_simulation(20, &[1, 20], true, |value: Number| value / 3)
+            .unwrap();
+
+        // Assert
+        assert_eq!(output.inspections, EXPECTED_INSPECTIONS);
+        assert_eq!(output.monkey_business(), 10605);
+        assert_eq!(
+            output
+                .snapshots
+                .iter()
+                .map(|snapshot| snapshot.round)
+                .collect::<Vec<_>>(),
+            [1, 20]
+        );
+        // after round 1 monkey 0 holds 4 items and monkey 1 holds the other 6.
+        let holders = |monkey| {
+            output.snapshots[0]
+                .locations
+                .iter()
+                .filter(|location| **location == monkey)
+                .count()
+        };
+        assert_eq!((holders(0), holders(1)), (4, 6));
+        // 79 is thrown from monkey 0 to monkey 3 with a worry level of 500, then on to monkey 1.
+        let paths = output.paths.as_ref().unwrap();
+        assert_eq!(paths[0][..3], [0, 3, 1]);
+        assert_eq!(
+            paths.iter().map(|path| path.len() - 1).sum::<usize>(),
+            EXPECTED_INSPECTIONS.iter().sum::<Number>() as usize
+        );
+    }
+
+    #[test]
+    fn test_predicted_inspections_match_simulation() {
+        // Arrange
+        let monkeys = INPUT.parse::<VecMonkey>().unwrap();
+        let modulus = monkeys.modulus().unwrap();
+        let relief = |value: Number| value % modulus;
+
+        // Act
+        let simulated = monkeys.run_simulation(1_000, &[], false, relief).unwrap();
+        let predicted = monkeys.predict_inspections(1_000, relief).unwrap();
+
+        // Assert
+        assert_eq!(predicted.inspections, simulated.inspections);
+        assert!(predicted.cycles.iter().all(|cycle| cycle.is_some()));
+    }
+
+    #[test]
+    fn test_predicted_part_2_inspections() {
+        // Arrange
+        const EXPECTED: [Number; 4] = [52166, 47830, 1938, 52013];
+
+        // Act
+        let output = predict_part_2(INPUT, 10_000).unwrap().inspections;
 
         // Assert
         assert_eq!(output, EXPECTED);
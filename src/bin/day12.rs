@@ -1,9 +1,91 @@
 const INPUT: &str = include_str!("../input/day12.txt");
 
+const USAGE: &str = "\
+usage: day12 [<ascii|ppm> [start|nearest]]
+       day12 route <dijkstra|astar> [<max climb> [<max descent|any> [<4|8> [<climb cost> [<descent cost>]]]]]";
+
 #[cfg(not(tarpaulin))]
 fn main() {
-    println!("Part 1 => {}", part_1(INPUT));
-    println!("Part 2 => {}", part_2(INPUT));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() {
+        println!("Part 1 => {}", part_1(INPUT));
+        println!("Part 2 => {}", part_2(INPUT));
+        return;
+    }
+    if let Err(error) = run(&args) {
+        eprintln!("{error}\n{USAGE}");
+        std::process::exit(2);
+    }
+}
+
+// draws a path or a route under a movement policy, an error being a message to print along with
+// the usage.
+#[cfg(not(tarpaulin))]
+fn run(args: &[String]) -> Result<(), String> {
+    use std::io::Write;
+
+    let puzzle = INPUT.parse::<Puzzle>().unwrap();
+    let output = if args[0] == "route" {
+        let search = match args.get(1).map(|search| search.as_str()) {
+            Some("dijkstra") => Search::Dijkstra,
+            Some("astar") => Search::AStar,
+            Some(other) => return Err(format!("unknown search {other:?}")),
+            None => return Err(String::from("expected a search")),
+        };
+        let default = MovementPolicy::default();
+        let policy = MovementPolicy {
+            max_climb: parse_or(args.get(2), default.max_climb)?,
+            max_descent: match args.get(3).map(|descent| descent.as_str()) {
+                None | Some("any") => None,
+                descent => Some(parse_or(descent, 0)?),
+            },
+            diagonals: match args.get(4).map(|neighbors| neighbors.as_str()) {
+                None | Some("4") => false,
+                Some("8") => true,
+                Some(other) => return Err(format!("{other:?} is not 4 or 8 neighbors")),
+            },
+            climb_cost: parse_or(args.get(5), default.climb_cost)?,
+            descent_cost: parse_or(args.get(6), default.descent_cost)?,
+            ..default
+        };
+        let puzzle = puzzle.with_policy(policy);
+        let route = puzzle
+            .get_cheapest_route_from_start(search)
+            .ok_or("the end can't be reached")?;
+        format!(
+            "Cost => {}\n{}",
+            route.cost,
+            puzzle.render_path(&route.path)
+        )
+        .into_bytes()
+    } else {
+        let path = match args.get(1).map(|route| route.as_str()) {
+            None | Some("start") => puzzle.get_path_from_start(),
+            Some("nearest") => puzzle
+                .get_nearest_start_of_height(0)
+                .and_then(|(coord, _)| puzzle.get_path_from(coord)),
+            Some(other) => return Err(format!("unknown route {other:?}")),
+        }
+        .ok_or("the end can't be reached")?;
+        match args[0].as_str() {
+            "ascii" => puzzle.render_path(&path).into_bytes(),
+            "ppm" => puzzle.render_path_ppm(&path, 4),
+            other => return Err(format!("unknown output format {other:?}")),
+        }
+    };
+    std::io::stdout()
+        .write_all(&output)
+        .map_err(|error| error.to_string())
+}
+
+// the number given on the command line, or the default when it's missing.
+#[cfg(not(tarpaulin))]
+fn parse_or<T: AsRef<str>>(arg: Option<T>, default: u32) -> Result<u32, String> {
+    arg.map_or(Ok(default), |arg| {
+        let arg = arg.as_ref();
+        arg.parse()
+            .map_err(|error| format!("invalid number {arg:?}: {error}"))
+    })
 }
 
 fn part_1(input: &str) -> u32 {
@@ -11,28 +93,94 @@ fn part_1(input: &str) -> u32 {
         .parse::<Puzzle>()
         .unwrap()
         .get_fewest_steps_from_start()
+        .unwrap()
 }
 
 fn part_2(input: &str) -> u32 {
-    let puzzle = input.parse::<Puzzle>().unwrap();
-    puzzle
-        .iter_cells()
-        .filter_map(|(coord, height)| {
-            if height == 0 {
-                Some(puzzle.get_fewest_steps_from(coord))
-            } else {
-                None
-            }
-        })
-        .min()
+    let (_, steps) = input
+        .parse::<Puzzle>()
         .unwrap()
+        .get_nearest_start_of_height(0)
+        .unwrap();
+    steps
 }
 
 mod private {
+    use core::cmp::Ordering;
     use core::convert::Infallible;
     use core::str::FromStr;
     use itertools::Either;
-    use std::collections::{HashMap, HashSet};
+    use std::cmp::Reverse;
+    use std::collections::{BinaryHeap, VecDeque};
+
+    // the rules for moving between cells. A step costs step_cost, plus climb_cost for each
+    // level climbed or descent_cost for each level descended. Costs saturate rather than
+    // overflow, so routes which would cost u32::MAX or more are treated as unreachable.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct MovementPolicy {
+        pub max_climb: u32,
+        // None for descending any amount.
+        pub max_descent: Option<u32>,
+        pub diagonals: bool,
+        pub step_cost: u32,
+        pub climb_cost: u32,
+        pub descent_cost: u32,
+    }
+
+    // the rules of the puzzle, climb at most 1 level and descend any amount in 4 directions,
+    // with every step costing the same.
+    impl Default for MovementPolicy {
+        fn default() -> Self {
+            Self {
+                max_climb: 1,
+                max_descent: None,
+                diagonals: false,
+                step_cost: 1,
+                climb_cost: 0,
+                descent_cost: 0,
+            }
+        }
+    }
+
+    impl MovementPolicy {
+        fn allows(&self, from_height: u32, to_height: u32) -> bool {
+            to_height <= from_height.saturating_add(self.max_climb)
+                && self
+                    .max_descent
+                    .is_none_or(|max_descent| from_height <= to_height.saturating_add(max_descent))
+        }
+
+        fn cost(&self, from_height: u32, to_height: u32) -> u32 {
+            self.step_cost
+                .saturating_add(
+                    self.climb_cost
+                        .saturating_mul(to_height.saturating_sub(from_height)),
+                )
+                .saturating_add(
+                    self.descent_cost
+                        .saturating_mul(from_height.saturating_sub(to_height)),
+                )
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Search {
+        Dijkstra,
+        AStar,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Route {
+        pub cost: u32,
+        pub path: Vec<(usize, usize)>,
+    }
+
+    // how a cell was reached during a search, previous is None for the cell the search began at.
+    #[derive(Clone, Copy, Debug)]
+    struct Visit {
+        steps: u32,
+        previous: Option<(usize, usize)>,
+    }
 
     #[derive(Debug, Default)]
     pub struct Puzzle {
@@ -41,9 +189,14 @@ mod private {
         height: usize,
         start_coord: (usize, usize),
         end_coord: (usize, usize),
+        policy: MovementPolicy,
     }
 
     impl Puzzle {
+        pub fn with_policy(self, policy: MovementPolicy) -> Self {
+            Self { policy, ..self }
+        }
+
         pub fn iter_cells(&self) -> impl Iterator<Item = ((usize, usize), u32)> + '_ {
             self.heightmap
                 .iter()
@@ -52,80 +205,266 @@ mod private {
                 .map(|(index, height)| (self.index_to_coord(index), height))
         }
 
-        pub fn get_fewest_steps_from_start(&self) -> u32 {
+        pub fn get_fewest_steps_from_start(&self) -> Option<u32> {
             self.get_fewest_steps_from(self.start_coord)
         }
 
-        pub fn get_fewest_steps_from(&self, coord: (usize, usize)) -> u32 {
-            // open set is the set of discovered nodes that need to be evaluated
-            // and possible expanded.
-            let mut open_set = HashSet::with_capacity(self.heightmap.len());
-            open_set.insert(coord);
-
-            // mapping which, for a given node coordinate will give the coordinate
-            // of the node immediately preceding it on the cheapest path from the start.
-            let mut came_from: HashMap<(usize, usize), (usize, usize)> =
-                HashMap::with_capacity(self.heightmap.len());
-
-            // for a given node coordinate, g_score[coord] will be the cost of the cheapest path from start to coord
-            // that is already known.
-            let mut g_score = (0..self.height)
-                .flat_map(|y_coord| {
-                    (0..self.width).map(move |x_coord| ((x_coord, y_coord), u32::MAX))
+        pub fn get_fewest_steps_from(&self, coord: (usize, usize)) -> Option<u32> {
+            let visits = self.breadth_first_search(coord, |coord| self.get_neighbor_iter(coord));
+            visits[self.coord_to_index(self.end_coord)].map(|visit| visit.steps)
+        }
+
+        // fewest steps to the end from every cell, found with a single search backwards from the
+        // end. None for cells the end can't be reached from.
+        pub fn get_steps_to_end(&self) -> Vec<Option<u32>> {
+            self.search_to_end()
+                .into_iter()
+                .map(|visit| visit.map(|visit| visit.steps))
+                .collect()
+        }
+
+        pub fn get_path_from_start(&self) -> Option<Vec<(usize, usize)>> {
+            self.get_path_from(self.start_coord)
+        }
+
+        // every cell on a shortest route to the end, including both the given cell and the end.
+        pub fn get_path_from(&self, coord: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+            // the backwards search links each cell to the next one along towards the end.
+            let visits = self.search_to_end();
+            let mut path = vec![coord];
+            let mut visit = visits[self.coord_to_index(coord)]?;
+            while let Some(next) = visit.previous {
+                path.push(next);
+                visit = visits[self.coord_to_index(next)].unwrap();
+            }
+            Some(path)
+        }
+
+        // the heightmap with each step of the path drawn as an arrow pointing to the next cell,
+        // ascii has no diagonal arrows so diagonal steps are drawn as slashes instead.
+        pub fn render_path(&self, path: &[(usize, usize)]) -> String {
+            let mut cells = self
+                .iter_cells()
+                .map(|(coord, height)| {
+                    if coord == self.start_coord {
+                        'S'
+                    } else if coord == self.end_coord {
+                        'E'
+                    } else {
+                        char::from(b'a' + height as u8)
+                    }
                 })
-                .collect::<HashMap<_, _>>();
-            g_score.insert(coord, 0);
-
-            // for a given node coordinate, f_score represents our current best guess to how cheap a complete path from
-            // start to finish, through n could be.
-            let mut f_score = g_score.clone();
-            f_score.insert(coord, self.estimate_remaining_distance(coord));
-
-            // while there are more nodes to expand.
-            while !open_set.is_empty() {
-                // find the coordinate in the open set with the lowest f_score to expand.
-                let (current, _) = open_set.iter().fold(
-                    ((0, 0), u32::MAX),
-                    |(lowest_coord, lowest_cost), coord| {
-                        let cost = f_score[coord];
-                        if cost < lowest_cost {
-                            (*coord, cost)
-                        } else {
-                            (lowest_coord, lowest_cost)
-                        }
-                    },
-                );
+                .collect::<Vec<_>>();
+            path.windows(2).for_each(|step| {
+                let ((from_x, from_y), (to_x, to_y)) = (step[0], step[1]);
+                cells[self.coord_to_index(step[0])] = match (to_x.cmp(&from_x), to_y.cmp(&from_y)) {
+                    (Ordering::Greater, Ordering::Equal) => '>',
+                    (Ordering::Less, Ordering::Equal) => '<',
+                    (Ordering::Equal, Ordering::Greater) => 'v',
+                    (Ordering::Equal, _) => '^',
+                    (Ordering::Greater, Ordering::Less) | (Ordering::Less, Ordering::Greater) => {
+                        '/'
+                    }
+                    _ => '\\',
+                };
+            });
+            cells
+                .chunks(self.width)
+                .map(|row| row.iter().chain(std::iter::once(&'\n')).collect::<String>())
+                .collect()
+        }
 
-                // if the current lowest is in fact the end point, we're done.
-                if current == self.end_coord {
-                    return get_steps_taken(came_from, current);
-                }
+        // binary PPM (P6) image with cells shaded from dark green at the bottom up to white at the
+        // top, the path drawn in red from a yellow first cell to a blue end.
+        pub fn render_path_ppm(&self, path: &[(usize, usize)], scale: usize) -> Vec<u8> {
+            let mut colours = self
+                .heightmap
+                .iter()
+                .map(|height| {
+                    let level = *height as f64 / 25.0;
+                    [(20.0, 235.0), (70.0, 235.0), (20.0, 235.0)]
+                        .map(|(from, to)| (from + (to - from) * level).round() as u8)
+                })
+                .collect::<Vec<_>>();
+            path.iter()
+                .for_each(|coord| colours[self.coord_to_index(*coord)] = [220, 30, 30]);
+            if let (Some(first), Some(last)) = (path.first(), path.last()) {
+                colours[self.coord_to_index(*first)] = [255, 200, 0];
+                colours[self.coord_to_index(*last)] = [40, 120, 255];
+            }
+            let mut image =
+                format!("P6\n{} {}\n255\n", self.width * scale, self.height * scale).into_bytes();
+            colours.chunks(self.width).for_each(|row| {
+                let row = row
+                    .iter()
+                    .flat_map(|colour| std::iter::repeat_n(*colour, scale).flatten())
+                    .collect::<Vec<_>>();
+                (0..scale).for_each(|_| image.extend(&row));
+            });
+            image
+        }
 
-                // otherwise remove current from the open set to expand it.
-                open_set.remove(&current);
+        pub fn get_cheapest_route_from_start(&self, search: Search) -> Option<Route> {
+            self.get_cheapest_route_from(self.start_coord, search)
+        }
 
-                // for each valid neighbor of the current coordinate
+        // the route to the end with the lowest total cost under the movement policy. Dijkstra and
+        // A* always agree on the cost, A* just expands fewer cells on the way.
+        pub fn get_cheapest_route_from(
+            &self,
+            coord: (usize, usize),
+            search: Search,
+        ) -> Option<Route> {
+            let estimate = |coord| match search {
+                Search::Dijkstra => 0,
+                Search::AStar => self.estimate_remaining_cost(coord),
+            };
+            let mut costs = vec![u32::MAX; self.heightmap.len()];
+            let mut came_from = vec![None; self.heightmap.len()];
+            let mut open = BinaryHeap::from([Reverse((estimate(coord), 0, coord))]);
+            costs[self.coord_to_index(coord)] = 0;
+            while let Some(Reverse((_, cost, current))) = open.pop() {
+                if current == self.end_coord {
+                    let mut path = vec![current];
+                    while let Some(previous) = came_from[self.coord_to_index(path[path.len() - 1])]
+                    {
+                        path.push(previous);
+                    }
+                    path.reverse();
+                    return Some(Route { cost, path });
+                }
+                // skip stale entries for cells which have since been reached more cheaply.
+                if cost > costs[self.coord_to_index(current)] {
+                    continue;
+                }
+                let current_height = self.heightmap[self.coord_to_index(current)];
                 self.get_neighbor_iter(current).for_each(|neighbor| {
-                    let tentative_g_score = g_score[&current] + 1; // distance from current to neighbor is always 1.
-                    if tentative_g_score < g_score[&neighbor] {
-                        came_from.insert(neighbor, current);
-                        g_score.insert(neighbor, tentative_g_score);
-                        f_score.insert(
+                    let index = self.coord_to_index(neighbor);
+                    let cost = cost
+                        .saturating_add(self.policy.cost(current_height, self.heightmap[index]));
+                    if cost < costs[index] {
+                        costs[index] = cost;
+                        came_from[index] = Some(current);
+                        open.push(Reverse((
+                            cost.saturating_add(estimate(neighbor)),
+                            cost,
                             neighbor,
-                            tentative_g_score + self.estimate_remaining_distance(neighbor),
-                        );
-                        open_set.insert(neighbor);
+                        )));
                     }
                 });
             }
+            None
+        }
+
+        // a lower bound on the cost of reaching the end, so A* stays admissible under any policy.
+        // Every route needs at least as many steps as the distance to the end (or as the climb or
+        // descent needs at the steepest allowed steps), and has to climb or descend at least the
+        // difference in height.
+        pub fn estimate_remaining_cost(&self, coord: (usize, usize)) -> u32 {
+            let ((x, y), (end_x, end_y)) = (coord, self.end_coord);
+            let (horizontal, vertical) = (x.abs_diff(end_x) as u32, y.abs_diff(end_y) as u32);
+            let distance = if self.policy.diagonals {
+                horizontal.max(vertical)
+            } else {
+                horizontal + vertical
+            };
+            let height = self.heightmap[self.coord_to_index(coord)];
+            let end_height = self.heightmap[self.coord_to_index(self.end_coord)];
+            let (climb, descent) = (
+                end_height.saturating_sub(height),
+                height.saturating_sub(end_height),
+            );
+            let climb_steps = Some(self.policy.max_climb)
+                .filter(|max_climb| *max_climb > 0)
+                .map_or(0, |max_climb| climb.div_ceil(max_climb));
+            let descent_steps = self
+                .policy
+                .max_descent
+                .filter(|max_descent| *max_descent > 0)
+                .map_or(0, |max_descent| descent.div_ceil(max_descent));
+            let steps = distance.max(climb_steps).max(descent_steps);
+            self.policy
+                .step_cost
+                .saturating_mul(steps)
+                .saturating_add(self.policy.climb_cost.saturating_mul(climb))
+                .saturating_add(self.policy.descent_cost.saturating_mul(descent))
+        }
 
-            // failed to find any valid path from start to end.
-            u32::MAX
+        fn search_to_end(&self) -> Vec<Option<Visit>> {
+            self.breadth_first_search(self.end_coord, |coord| {
+                self.get_reverse_neighbor_iter(coord)
+            })
+        }
+
+        // the cell of the given height closest to the end, along with how many steps it is away.
+        pub fn get_nearest_start_of_height(&self, height: u32) -> Option<((usize, usize), u32)> {
+            self.get_steps_to_end()
+                .into_iter()
+                .zip(self.iter_cells())
+                .filter_map(|(steps, (coord, cell_height))| {
+                    if cell_height == height {
+                        Some((coord, steps?))
+                    } else {
+                        None
+                    }
+                })
+                .min_by_key(|(_, steps)| *steps)
+        }
+
+        // how each cell (by index) was first reached from the source, None if it can't be reached.
+        fn breadth_first_search<I: Iterator<Item = (usize, usize)>>(
+            &self,
+            source: (usize, usize),
+            get_neighbors: impl Fn((usize, usize)) -> I,
+        ) -> Vec<Option<Visit>> {
+            let mut visits = vec![None; self.heightmap.len()];
+            let mut queue = VecDeque::from([source]);
+            visits[self.coord_to_index(source)] = Some(Visit {
+                steps: 0,
+                previous: None,
+            });
+            while let Some(current) = queue.pop_front() {
+                let steps = visits[self.coord_to_index(current)].unwrap().steps + 1;
+                get_neighbors(current).for_each(|neighbor| {
+                    let index = self.coord_to_index(neighbor);
+                    if visits[index].is_none() {
+                        visits[index] = Some(Visit {
+                            steps,
+                            previous: Some(current),
+                        });
+                        queue.push_back(neighbor);
+                    }
+                });
+            }
+            visits
         }
 
         fn get_neighbor_iter(
             &self,
             coord: (usize, usize),
+        ) -> impl Iterator<Item = (usize, usize)> + '_ {
+            let current_height = self.heightmap[self.coord_to_index(coord)];
+            self.get_adjacent_iter(coord).filter(move |neighbor| {
+                let neighbor_height = self.heightmap[self.coord_to_index(*neighbor)];
+                self.policy.allows(current_height, neighbor_height)
+            })
+        }
+
+        // the cells which can step onto the given cell.
+        fn get_reverse_neighbor_iter(
+            &self,
+            coord: (usize, usize),
+        ) -> impl Iterator<Item = (usize, usize)> + '_ {
+            let current_height = self.heightmap[self.coord_to_index(coord)];
+            self.get_adjacent_iter(coord).filter(move |neighbor| {
+                let neighbor_height = self.heightmap[self.coord_to_index(*neighbor)];
+                self.policy.allows(neighbor_height, current_height)
+            })
+        }
+
+        fn get_adjacent_iter(
+            &self,
+            coord: (usize, usize),
         ) -> impl Iterator<Item = (usize, usize)> + '_ {
             let (x, y) = coord;
             let left_neigbor = if x > 0 {
@@ -148,15 +487,21 @@ mod private {
             } else {
                 Either::Right(std::iter::empty())
             };
+            let diagonal_neighbors = if self.policy.diagonals {
+                Either::Left([(-1, -1), (1, -1), (1, 1), (-1, 1)].into_iter().filter_map(
+                    move |(dx, dy)| {
+                        let neighbor = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+                        (neighbor.0 < self.width && neighbor.1 < self.height).then_some(neighbor)
+                    },
+                ))
+            } else {
+                Either::Right(std::iter::empty())
+            };
             left_neigbor
                 .chain(top_neigbor)
                 .chain(right_neigbor)
                 .chain(bottom_neigbor)
-                .filter(move |neighbor| {
-                    let current_height = self.heightmap[self.coord_to_index(coord)];
-                    let neighbor_height = self.heightmap[self.coord_to_index(*neighbor)];
-                    neighbor_height <= current_height + 1
-                })
+                .chain(diagonal_neighbors)
         }
 
         fn coord_to_index(&self, coord: (usize, usize)) -> usize {
@@ -169,26 +514,6 @@ mod private {
             let x = index % self.width;
             (x, y)
         }
-
-        fn estimate_remaining_distance(&self, from_coord: (usize, usize)) -> u32 {
-            let (from_x, from_y) = from_coord;
-            let (to_x, to_y) = self.end_coord;
-            let horizontal_distance = (from_x as i64 - to_x as i64).unsigned_abs() as u32;
-            let vertical_distance = (from_y as i64 - to_y as i64).unsigned_abs() as u32;
-            horizontal_distance + vertical_distance
-        }
-    }
-
-    fn get_steps_taken(
-        came_from: HashMap<(usize, usize), (usize, usize)>,
-        mut current: (usize, usize),
-    ) -> u32 {
-        let mut steps_taken = 0;
-        while let Some(came_from) = came_from.get(&current) {
-            current = *came_from;
-            steps_taken += 1;
-        }
-        steps_taken
     }
 
     impl FromStr for Puzzle {
@@ -262,4 +587,326 @@ mod tests {
         // Assert
         assert_eq!(output, EXPECTED);
     }
+
+    #[test]
+    fn test_unreachable_end() {
+        // Arrange
+        const INPUT: &str = "
+        Sbz
+        abE
+        ";
+
+        // Act
+        let puzzle = INPUT.parse::<Puzzle>().unwrap();
+        let output = (
+            puzzle.get_fewest_steps_from_start(),
+            puzzle.get_nearest_start_of_height(0),
+        );
+
+        // Assert
+        assert_eq!(output, (None, None));
+    }
+
+    #[test]
+    fn test_nearest_start_of_height() {
+        // Arrange
+        const EXPECTED: [Option<((usize, usize), u32)>; 3] =
+            [Some(((0, 4), 29)), Some(((1, 4), 28)), Some(((2, 3), 26))];
+
+        // Act
+        let puzzle = INPUT.parse::<Puzzle>().unwrap();
+        let output = [0, 1, 2].map(|height| puzzle.get_nearest_start_of_height(height));
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_steps_to_end_match_forward_searches() {
+        // Arrange
+        let puzzle = INPUT.parse::<Puzzle>().unwrap();
+
+        // Act
+        let output = puzzle.get_steps_to_end();
+
+        // Assert
+        puzzle
+            .iter_cells()
+            .zip(output)
+            .for_each(|((coord, _), steps)| {
+                assert_eq!(steps, puzzle.get_fewest_steps_from(coord));
+            });
+    }
+
+    #[test]
+    fn test_path_from_start() {
+        // Arrange
+        let puzzle = INPUT.parse::<Puzzle>().unwrap();
+
+        // Act
+        let output = puzzle.get_path_from_start().unwrap();
+
+        // Assert
+        assert_eq!(output.len(), 32);
+        assert_eq!((output[0], output[31]), ((0, 0), (5, 2)));
+        output.windows(2).for_each(|step| {
+            let ((from_x, from_y), (to_x, to_y)) = (step[0], step[1]);
+            assert_eq!(from_x.abs_diff(to_x) + from_y.abs_diff(to_y), 1);
+        });
+    }
+
+    #[test]
+    fn test_render_path() {
+        // Arrange
+        const EXPECTED: &str = "\
+v..v<<<<
+>v.vv<<^
+.>vv>E^^
+..v>>>^^
+..>>>>>^
+";
+        // the route drawn in the puzzle description, with the cells off the route blanked out.
+        let path = [
+            (0, 0),
+            (0, 1),
+            (1, 1),
+            (1, 2),
+            (2, 2),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+            (4, 4),
+            (5, 4),
+            (6, 4),
+            (7, 4),
+            (7, 3),
+            (7, 2),
+            (7, 1),
+            (7, 0),
+            (6, 0),
+            (5, 0),
+            (4, 0),
+            (3, 0),
+            (3, 1),
+            (3, 2),
+            (3, 3),
+            (4, 3),
+            (5, 3),
+            (6, 3),
+            (6, 2),
+            (6, 1),
+            (5, 1),
+            (4, 1),
+            (4, 2),
+            (5, 2),
+        ];
+        let puzzle = INPUT.parse::<Puzzle>().unwrap();
+
+        // Act
+        let output = puzzle
+            .render_path(&path)
+            .lines()
+            .zip(INPUT.trim().lines())
+            .map(|(rendered, heights)| {
+                rendered
+                    .chars()
+                    .zip(heights.trim().chars())
+                    .map(|(cell, height)| {
+                        if cell == height && cell != 'E' {
+                            '.'
+                        } else {
+                            cell
+                        }
+                    })
+                    .chain(std::iter::once('\n'))
+                    .collect::<String>()
+            })
+            .collect::<String>();
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_render_path_ppm() {
+        // Arrange
+        let puzzle = INPUT.parse::<Puzzle>().unwrap();
+        let path = puzzle.get_path_from_start().unwrap();
+        let header = b"P6\n16 10\n255\n";
+
+        // Act
+        let output = puzzle.render_path_ppm(&path, 2);
+
+        // Assert
+        assert_eq!(&output[..header.len()], header);
+        assert_eq!(output.len(), header.len() + 16 * 10 * 3);
+        // the start is the top left 2x2 block, and the cell right of it isn't on the path.
+        assert_eq!(
+            output[header.len()..header.len() + 6],
+            [255, 200, 0, 255, 200, 0]
+        );
+        assert_eq!(output[header.len() + 6..header.len() + 9], [20, 70, 20]);
+    }
+
+    // a random heightmap where neighbouring cells are mostly within a couple of levels of each other.
+    fn generate_heightmap(width: usize, height: usize, seed: u64) -> String {
+        let mut state = seed;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 33) as i64
+        };
+        let mut heights = vec![0_i64; width * height];
+        (0..width * height).for_each(|index| {
+            let (x, y) = (index % width, index / width);
+            let neighbor = match (x, y) {
+                (0, 0) => 0,
+                (_, 0) => heights[index - 1],
+                (0, _) => heights[index - width],
+                _ => (heights[index - 1] + heights[index - width]) / 2,
+            };
+            heights[index] = (neighbor + next() % 5 - 1).clamp(0, 25);
+        });
+        heights
+            .chunks(width)
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, level)| match (x, y) {
+                        (0, 0) => 'S',
+                        _ if (x, y) == (width - 1, height - 1) => 'E',
+                        _ => char::from(b'a' + *level as u8),
+                    })
+                    .chain(std::iter::once('\n'))
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    const POLICIES: [MovementPolicy; 4] = [
+        MovementPolicy {
+            max_climb: 1,
+            max_descent: None,
+            diagonals: false,
+            step_cost: 1,
+            climb_cost: 0,
+            descent_cost: 0,
+        },
+        MovementPolicy {
+            max_climb: 2,
+            max_descent: Some(3),
+            diagonals: true,
+            step_cost: 2,
+            climb_cost: 5,
+            descent_cost: 1,
+        },
+        MovementPolicy {
+            max_climb: 3,
+            max_descent: Some(1),
+            diagonals: false,
+            step_cost: 1,
+            climb_cost: 10,
+            descent_cost: 0,
+        },
+        MovementPolicy {
+            max_climb: 25,
+            max_descent: Some(0),
+            diagonals: true,
+            step_cost: 0,
+            climb_cost: 1,
+            descent_cost: 100,
+        },
+    ];
+
+    #[test]
+    fn test_cheapest_route_under_puzzle_rules() {
+        // Arrange
+        let puzzle = INPUT.parse::<Puzzle>().unwrap();
+
+        // Act
+        let output = [Search::Dijkstra, Search::AStar]
+            .map(|search| puzzle.get_cheapest_route_from_start(search).unwrap());
+
+        // Assert
+        assert_eq!((output[0].cost, output[1].cost), (31, 31));
+        assert_eq!(output[0].path.len(), 32);
+    }
+
+    #[test]
+    fn test_cheapest_route_under_policies() {
+        // Arrange
+        const EXPECTED: [Option<u32>; 4] = [Some(31), Some(163), Some(277), Some(25)];
+
+        // Act
+        let output = POLICIES.map(|policy| {
+            let puzzle = INPUT.parse::<Puzzle>().unwrap().with_policy(policy);
+            puzzle
+                .get_cheapest_route_from_start(Search::Dijkstra)
+                .map(|route| route.cost)
+        });
+
+        // Assert
+        assert_eq!(output, EXPECTED);
+    }
+
+    #[test]
+    fn test_extreme_policy_costs_saturate() {
+        // Arrange
+        const EXPECTED: [Option<u32>; 2] = [None, Some(7 * (u32::MAX / 64))];
+        let unlimited = MovementPolicy {
+            max_climb: u32::MAX,
+            max_descent: Some(u32::MAX),
+            ..MovementPolicy::default()
+        };
+        let policies = [
+            MovementPolicy {
+                climb_cost: u32::MAX,
+                descent_cost: u32::MAX,
+                ..unlimited
+            },
+            MovementPolicy {
+                step_cost: u32::MAX / 64,
+                ..unlimited
+            },
+        ];
+
+        // Act
+        let output = policies.map(|policy| {
+            let puzzle = INPUT.parse::<Puzzle>().unwrap().with_policy(policy);
+            [Search::Dijkstra, Search::AStar].map(|search| {
+                puzzle
+                    .get_cheapest_route_from_start(search)
+                    .map(|route| route.cost)
+            })
+        });
+
+        // Assert
+        assert_eq!(output, EXPECTED.map(|cost| [cost; 2]));
+    }
+
+    #[test]
+    fn test_a_star_heuristic_is_admissible() {
+        POLICIES.into_iter().enumerate().for_each(|(seed, policy)| {
+            // Arrange
+            let puzzle = generate_heightmap(24, 16, seed as u64)
+                .parse::<Puzzle>()
+                .unwrap()
+                .with_policy(policy);
+
+            // Act / Assert
+            puzzle.iter_cells().for_each(|(coord, _)| {
+                let dijkstra = puzzle.get_cheapest_route_from(coord, Search::Dijkstra);
+                let a_star = puzzle.get_cheapest_route_from(coord, Search::AStar);
+                assert_eq!(
+                    dijkstra.as_ref().map(|route| route.cost),
+                    a_star.as_ref().map(|route| route.cost)
+                );
+                if let Some(route) = dijkstra {
+                    assert!(puzzle.estimate_remaining_cost(coord) <= route.cost);
+                }
+            });
+        });
+    }
 }
@@ -96,7 +96,7 @@ fn part_2(input: &str) -> usize {
             }
         })
         .collect::<Vec<_>>();
-    let markers = vec![parse_input("[[2]]"), parse_input("[[6]]")];
+    let markers = [parse_input("[[2]]"), parse_input("[[6]]")];
     elements.extend(markers.iter().cloned());
     elements.sort();
     elements
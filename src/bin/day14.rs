@@ -177,8 +177,7 @@ impl<const ADD_FLOOR: bool> FromStr for Cave<ADD_FLOOR> {
         );
         let dims = bounds.dims();
         let total = dims.0 * dims.1;
-        let mut data = std::iter::repeat(Element::Air)
-            .take(total)
+        let mut data = std::iter::repeat_n(Element::Air, total)
             .collect::<Vec<_>>()
             .into_boxed_slice();
         let stride = dims.0;
@@ -353,14 +353,10 @@ mod private {
             fn ensure_chamber_height(&mut self, height: usize) {
                 let current_height = self.state.len();
                 if height > current_height {
-                    self.state.extend(
-                        std::iter::repeat(
-                            std::iter::repeat(Space::Empty)
-                                .take(self.width)
-                                .collect::<Vec<_>>(),
-                        )
-                        .take(height - current_height),
-                    );
+                    self.state.extend(std::iter::repeat_n(
+                        std::iter::repeat_n(Space::Empty, self.width).collect::<Vec<_>>(),
+                        height - current_height,
+                    ));
                 }
             }
         }
@@ -50,11 +50,11 @@ fn part_2(input: &str) -> NumericType {
     }
 }
 
-fn parse_input(input: &str) -> HashMap<&str, Monkey> {
+fn parse_input(input: &str) -> HashMap<&str, Monkey<'_>> {
     input.trim().lines().map(parse_line).collect()
 }
 
-fn parse_line(input: &str) -> (&str, Monkey) {
+fn parse_line(input: &str) -> (&str, Monkey<'_>) {
     let (name, expression) = input.trim().split_once(": ").unwrap();
     let monkey = if let Some((operand_1, operand_2)) = expression.split_once(" + ") {
         Monkey::Expression(ExpressionType::Add, operand_1, operand_2)